@@ -1,13 +1,64 @@
 #[derive(Subcommand)]
 pub enum PhpCommand {
     /// Install a PHP version
-    Install { version: String },
+    Install {
+        version: String,
+        /// Install without checking the download's checksum or signature
+        #[arg(long)]
+        skip_verify: bool,
+    },
+    /// Remove an installed PHP version
+    Uninstall {
+        version: String,
+        /// Move recipes still using it to another installed version
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove PHP versions no recipe uses
+    Prune,
     /// List installed PHP versions
     List,
     /// Use a PHP version for this project
     Use { version: String },
+    /// Register PHP versions already installed on this machine
+    Scan,
+    /// Fetch the latest PHP index
+    UpdateIndex {
+        /// Index https URL or local file; defaults to php_index_url in config.yml
+        source: Option<String>,
+    },
+    /// php.ini settings Furnace applies to a version
+    Ini {
+        version: String,
+        #[command(subcommand)]
+        command: IniCommand,
+    },
+    /// Enable or disable PHP extensions
+    Ext {
+        #[command(subcommand)]
+        command: ExtCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum IniCommand {
+    /// Set a directive, e.g. memory_limit=512M
+    Set { setting: String },
+    /// List directives set through Furnace
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ExtCommand {
+    /// Load an extension for a PHP version
+    Enable { version: String, extension: String },
+    /// Stop loading an extension Furnace enabled
+    Disable { version: String, extension: String },
+    /// List extensions available to a PHP version
+    List { version: String },
 }
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 
 #[derive(Parser)]
@@ -21,6 +72,9 @@ use clap::{Parser, Subcommand};
 Powerful, hot, ready to cook your code.
         "#)]
 pub struct Cli {
+    /// Furnace home directory (defaults to $FURNACE_HOME or ~/.furnace)
+    #[arg(long, global = true)]
+    pub home: Option<PathBuf>,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -29,11 +83,34 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     /// Start services
-    Serve,
+    Serve {
+        /// Kill processes already listening on the HTTP/HTTPS ports
+        #[arg(long)]
+        force: bool,
+        /// Stay attached, restarting services that crash, until Ctrl-C
+        #[arg(long)]
+        foreground: bool,
+    },
     /// Stop all Furnace services
     Stop,
+    /// Keep services supervised in the background and serve the control API
+    Daemon,
+    /// Follow project and template changes, regenerating configs as needed
+    Watch,
+    /// Show the end of a service log
+    Logs {
+        /// nginx, apache, dnsmasq, php-<version> or a recipe name
+        service: String,
+        /// Number of lines to show
+        #[arg(short = 'n', long, default_value_t = 50)]
+        lines: usize,
+    },
     /// Restart Furnace services
-    Restart,
+    Restart {
+        /// Kill processes already listening on the HTTP/HTTPS ports
+        #[arg(long)]
+        force: bool,
+    },
     /// Remove current project from Furnace management
     Dispose {
         /// Optionally set the project name to dispose
@@ -42,8 +119,22 @@ pub enum Commands {
     },
     /// Install dependencies/services
     Install,
+    /// Serve a recipe over HTTPS with a certificate from the Furnace CA
+    Secure {
+        /// Recipe to secure; defaults to the current project
+        name: Option<String>,
+    },
+    /// Serve a recipe over plain HTTP again
+    Unsecure {
+        /// Recipe to unsecure; defaults to the current project
+        name: Option<String>,
+    },
     /// Show status of services
-    Status,
+    Status {
+        /// Print the status as JSON
+        #[arg(long)]
+        json: bool,
+    },
     /// Recipe management
     Recipe {
         #[command(subcommand)]
@@ -59,6 +150,21 @@ pub enum Commands {
         #[command(subcommand)]
         command: PhpCommand,
     },
+    /// Bundled config templates and PHP repository
+    Assets {
+        #[command(subcommand)]
+        command: AssetsCommand,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AssetsCommand {
+    /// Write the bundled defaults to ~/.furnace for customization
+    Extract {
+        /// Overwrite files that already exist
+        #[arg(long)]
+        force: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -69,11 +175,18 @@ pub enum RecipeCommand {
 
 #[derive(Subcommand)]
 pub enum CookCommand {
-    /// Cook a recipe from the current directory (Laravel)
+    /// Cook a recipe from the current directory
     Here {
         /// Optionally set the project name
         #[arg(long)]
         name: Option<String>,
+        /// Web server to serve the project with (nginx or apache)
+        #[arg(long)]
+        serve_with: Option<String>,
+        /// Project driver to use instead of detecting one
+        /// (laravel, symfony, wordpress, drupal, craftcms, static or php)
+        #[arg(long)]
+        driver: Option<String>,
     },
     /// Dispose a recipe (optionally by name)
     Dispose {
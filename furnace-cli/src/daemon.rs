@@ -0,0 +1,303 @@
+//! `furnace daemon`: keeps services supervised and serves the control API
+//! that `furnace_core::daemon::DaemonClient` talks to.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use furnace_core::daemon::{
+    CookRequest, DaemonInfo, ErrorResponse, LogsResponse, PhpUninstallRequest, PhpUseRequest, StartRequest,
+};
+use furnace_core::process::StopResult;
+use furnace_core::recipe::{self, CookOptions, DisposeReport, Recipe, RecipeDisposalTarget};
+use furnace_core::status::FurnaceStatus;
+use furnace_core::supervisor::Supervisor;
+use furnace_core::php::UninstallReport;
+use furnace_core::{logs, php, php_installation, services, ErrorKind, FurnaceError, FurnacePaths};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+#[derive(Clone)]
+struct AppState {
+    paths: FurnacePaths,
+    token: String,
+    supervisor: Arc<Mutex<Option<Supervisor>>>,
+}
+
+struct ApiError(FurnaceError);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let kind = self.0.kind();
+        let status = match kind {
+            ErrorKind::NotFound => StatusCode::NOT_FOUND,
+            ErrorKind::Invalid | ErrorKind::Config => StatusCode::BAD_REQUEST,
+            ErrorKind::Conflict => StatusCode::CONFLICT,
+            ErrorKind::Unavailable | ErrorKind::Internal | ErrorKind::Io => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, Json(ErrorResponse { error: self.0.to_string(), kind })).into_response()
+    }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+/// Runs blocking core code off the async runtime.
+async fn blocking<T, F>(f: F) -> ApiResult<T>
+where
+    F: FnOnce() -> Result<T, FurnaceError> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| ApiError(FurnaceError::Daemon(e.to_string())))?
+        .map(Json)
+        .map_err(ApiError)
+}
+
+/// Starts services, then serves the API until Ctrl-C or SIGTERM.
+pub fn run(paths: FurnacePaths) -> Result<(), FurnaceError> {
+    if DaemonInfo::load(&paths).is_some() {
+        return Err(FurnaceError::AlreadyRunning("furnace daemon".to_string()));
+    }
+    let state = AppState {
+        paths: paths.clone(),
+        token: uuid::Uuid::new_v4().simple().to_string(),
+        supervisor: Arc::new(Mutex::new(None)),
+    };
+    match services::start_supervised(&paths, false) {
+        Ok(supervisor) => *state.supervisor.lock().unwrap() = Some(supervisor),
+        Err(e) => error!("Failed to start services: {e}"),
+    }
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let poller = {
+        let supervisor = state.supervisor.clone();
+        let shutdown = shutdown.clone();
+        std::thread::spawn(move || {
+            while !shutdown.load(Ordering::SeqCst) {
+                if let Some(supervisor) = supervisor.lock().unwrap().as_mut() {
+                    supervisor.poll();
+                }
+                std::thread::sleep(Duration::from_millis(500));
+            }
+        })
+    };
+
+    let watcher = {
+        let paths = paths.clone();
+        let shutdown = shutdown.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = crate::watcher::watch(&paths, &shutdown) {
+                error!("File watcher stopped: {e}");
+            }
+        })
+    };
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let served = runtime.block_on(async {
+        let shutdown_signal = signal()?;
+        let listener = tokio::net::TcpListener::bind(("127.0.0.1", 0)).await?;
+        let port = listener.local_addr()?.port();
+        DaemonInfo { pid: std::process::id(), port, token: state.token.clone() }.write(&paths)?;
+        println!("Furnace daemon listening on 127.0.0.1:{}; press Ctrl-C to stop.", port);
+        axum::serve(listener, router(state.clone()))
+            .with_graceful_shutdown(shutdown_signal)
+            .await?;
+        Ok::<(), FurnaceError>(())
+    });
+
+    shutdown.store(true, Ordering::SeqCst);
+    let _ = poller.join();
+    let _ = watcher.join();
+    if let Some(mut supervisor) = state.supervisor.lock().unwrap().take() {
+        supervisor.stop_all();
+    }
+    DaemonInfo::remove(&paths)?;
+    info!("Furnace daemon stopped");
+    served
+}
+
+fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/recipes/cook", post(cook))
+        .route("/recipes/dispose", post(dispose))
+        .route("/php/use", post(php_use))
+        .route("/php/uninstall", post(php_uninstall))
+        .route("/php/prune", post(php_prune))
+        .route("/services/start", post(start))
+        .route("/services/stop", post(stop))
+        .route("/services/restart", post(restart))
+        .route("/logs/:service", get(logs))
+        .layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+/// Rejects requests without the token from daemon.json.
+async fn authorize(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let expected = format!("Bearer {}", state.token);
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v == expected);
+    if !authorized {
+        let body = ErrorResponse { error: "missing or invalid token".to_string(), kind: ErrorKind::Config };
+        return (StatusCode::UNAUTHORIZED, Json(body)).into_response();
+    }
+    next.run(request).await
+}
+
+/// Resolves on Ctrl-C or SIGTERM. Registering for SIGTERM happens up front
+/// so a failure stops the daemon from starting.
+fn signal() -> std::io::Result<impl std::future::Future<Output = ()>> {
+    #[cfg(unix)]
+    let mut term = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+    Ok(async move {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = term.recv() => {}
+        }
+        #[cfg(not(unix))]
+        let _ = tokio::signal::ctrl_c().await;
+    })
+}
+
+async fn status(State(state): State<AppState>) -> ApiResult<FurnaceStatus> {
+    blocking(move || services::status(&state.paths)).await
+}
+
+async fn cook(State(state): State<AppState>, Json(request): Json<CookRequest>) -> ApiResult<Recipe> {
+    blocking(move || {
+        let opts = CookOptions { name: request.name, serve_with: request.serve_with, driver: request.driver };
+        recipe::cook(&state.paths, &request.path, opts)
+    })
+    .await
+}
+
+async fn dispose(State(state): State<AppState>, Json(target): Json<RecipeDisposalTarget>) -> ApiResult<DisposeReport> {
+    blocking(move || recipe::dispose(&state.paths, target)).await
+}
+
+async fn php_use(State(state): State<AppState>, Json(request): Json<PhpUseRequest>) -> ApiResult<()> {
+    blocking(move || php::php_use(&state.paths, &request.path, &request.version)).await
+}
+
+async fn php_uninstall(
+    State(state): State<AppState>,
+    Json(request): Json<PhpUninstallRequest>,
+) -> ApiResult<UninstallReport> {
+    blocking(move || {
+        let mut supervisor = state.supervisor.lock().unwrap();
+        uninstall_php(&state.paths, supervisor.as_mut(), &request.version, request.force)
+    })
+    .await
+}
+
+async fn php_prune(State(state): State<AppState>) -> ApiResult<Vec<UninstallReport>> {
+    blocking(move || {
+        let mut supervisor = state.supervisor.lock().unwrap();
+        php::unused_versions(&state.paths)?
+            .iter()
+            .map(|version| uninstall_php(&state.paths, supervisor.as_mut(), version, false))
+            .collect()
+    })
+    .await
+}
+
+async fn start(State(state): State<AppState>, Json(request): Json<StartRequest>) -> ApiResult<()> {
+    blocking(move || start_services(&state, request.force)).await
+}
+
+async fn stop(State(state): State<AppState>) -> ApiResult<Vec<StopResult>> {
+    blocking(move || stop_services(&state)).await
+}
+
+async fn restart(State(state): State<AppState>, Json(request): Json<StartRequest>) -> ApiResult<()> {
+    blocking(move || {
+        for result in stop_services(&state)? {
+            info!("{}", result);
+        }
+        start_services(&state, request.force)
+    })
+    .await
+}
+
+#[derive(Deserialize)]
+struct LogsQuery {
+    #[serde(default = "default_lines")]
+    lines: usize,
+}
+
+fn default_lines() -> usize {
+    50
+}
+
+async fn logs(
+    State(state): State<AppState>,
+    Path(service): Path<String>,
+    Query(query): Query<LogsQuery>,
+) -> ApiResult<LogsResponse> {
+    blocking(move || {
+        let lines = logs::tail(&state.paths, &service, query.lines)?;
+        Ok(LogsResponse { service, lines })
+    })
+    .await
+}
+
+/// Takes the version's FPM master away from the supervisor before
+/// uninstalling, since a supervised child that exits lingers as a zombie
+/// that still looks alive, and puts it back if the uninstall fails.
+fn uninstall_php(
+    paths: &FurnacePaths,
+    mut supervisor: Option<&mut Supervisor>,
+    version: &str,
+    force: bool,
+) -> Result<UninstallReport, FurnaceError> {
+    let installation = php_installation::installation(paths, version)?;
+    let supervised = supervisor
+        .as_deref_mut()
+        .and_then(|s| s.remove(&format!("php-fpm {}", version)));
+    if let Some(stopped) = &supervised {
+        info!("{}", stopped);
+    }
+    let uninstalled = php::php_uninstall(paths, version, force);
+    if let (Err(_), Some(supervisor), Some(_)) = (&uninstalled, supervisor, supervised) {
+        let respawned = services::php_fpm_spec(paths, &installation)
+            .and_then(|spec| spec.map_or(Ok(()), |spec| supervisor.spawn(spec)));
+        if let Err(e) = respawned {
+            warn!("Failed to restart PHP-FPM {}: {e}", version);
+        }
+    }
+    uninstalled
+}
+
+fn start_services(state: &AppState, force: bool) -> Result<(), FurnaceError> {
+    let mut supervisor = state.supervisor.lock().unwrap();
+    if supervisor.is_some() {
+        return Err(FurnaceError::AlreadyRunning("Furnace services".to_string()));
+    }
+    *supervisor = Some(services::start_supervised(&state.paths, force)?);
+    Ok(())
+}
+
+/// Stops supervised services, then anything else Furnace left running.
+fn stop_services(state: &AppState) -> Result<Vec<StopResult>, FurnaceError> {
+    let mut results = match state.supervisor.lock().unwrap().take() {
+        Some(mut supervisor) => supervisor.stop_all(),
+        None => Vec::new(),
+    };
+    for result in services::stop(&state.paths)? {
+        if !results.iter().any(|r| r.name == result.name) {
+            results.push(result);
+        }
+    }
+    Ok(results)
+}
@@ -1,48 +1,311 @@
 mod cli;
-mod config;
-mod platform;
-// mod services;
-// mod recipe;
-// mod php;
-mod nginx_util;
+mod daemon;
+mod report;
+mod watcher;
 use clap::Parser;
-use tracing_subscriber;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 // Use core business logic
-use furnace_core::{services, recipe, php};
+use furnace_core::daemon::{CookRequest, DaemonClient, PhpUninstallRequest, PhpUseRequest};
+use furnace_core::{assets, config::FurnaceConfig, logs, services, recipe, php, php_index, php_ini, php_installation, tls, ErrorKind, FurnaceError, FurnacePaths};
 
 fn main() {
     tracing_subscriber::fmt::init();
 
     let cli = cli::Cli::parse();
 
+    if let Err(e) = run(&cli) {
+        eprintln!("Error: {e}");
+        std::process::exit(exit_code(&e));
+    }
+}
+
+fn run(cli: &cli::Cli) -> Result<(), FurnaceError> {
+    let paths = FurnacePaths::resolve(cli.home.clone())?;
     match &cli.command {
-        cli::Commands::Serve => services::serve(),
-        cli::Commands::Stop => services::stop(),
-        cli::Commands::Restart => services::restart(),
-        cli::Commands::Dispose { name } => recipe::dispose_recipe_cli(name.clone()),
-        cli::Commands::Install => services::install(),
-        cli::Commands::Status => services::status(),
+        cli::Commands::Serve { force, foreground: false } => match DaemonClient::connect(&paths) {
+            Some(client) => client.start(*force),
+            None => services::serve(&paths, *force),
+        },
+        cli::Commands::Serve { force, foreground: true } => {
+            if DaemonClient::connect(&paths).is_some() {
+                return Err(FurnaceError::AlreadyRunning("furnace daemon".to_string()));
+            }
+            let shutdown = shutdown_flag()?;
+            let mut supervisor = services::start_supervised(&paths, *force)?;
+            println!("Furnace is running in the foreground; press Ctrl-C to stop.");
+            supervisor.run(&shutdown);
+            Ok(())
+        }
+        cli::Commands::Stop => {
+            let results = match DaemonClient::connect(&paths) {
+                Some(client) => client.stop()?,
+                None => services::stop(&paths)?,
+            };
+            for result in &results {
+                println!("{}", result);
+            }
+            Ok(())
+        }
+        cli::Commands::Restart { force } => match DaemonClient::connect(&paths) {
+            Some(client) => client.restart(*force),
+            None => services::restart(&paths, *force),
+        },
+        cli::Commands::Daemon => daemon::run(paths),
+        cli::Commands::Watch => watcher::watch(&paths, &*shutdown_flag()?),
+        cli::Commands::Logs { service, lines } => {
+            let lines = match DaemonClient::connect(&paths) {
+                Some(client) => client.logs(service, *lines)?.lines,
+                None => logs::tail(&paths, service, *lines)?,
+            };
+            for line in lines {
+                println!("{}", line);
+            }
+            Ok(())
+        }
+        cli::Commands::Dispose { name } => dispose(&paths, name.clone()),
+        cli::Commands::Install => services::install(&paths),
+        cli::Commands::Secure { name } => {
+            let name = recipe_name(&paths, name.clone())?;
+            let report = recipe::secure(&paths, &name)?;
+            println!("{} is now served at {}", report.recipe.name, report.recipe.url(&FurnaceConfig::load(&paths)?));
+            println!("Certificate written to {}", report.cert.display());
+            if report.ca_created {
+                println!("Created the Furnace root CA. Trust it once so browsers accept Furnace certificates:");
+                println!("  {}", tls::trust_command(&paths));
+            }
+            Ok(())
+        }
+        cli::Commands::Unsecure { name } => {
+            let name = recipe_name(&paths, name.clone())?;
+            let recipe = recipe::unsecure(&paths, &name)?;
+            println!("{} is now served at {}", recipe.name, recipe.url(&FurnaceConfig::load(&paths)?));
+            Ok(())
+        }
+        cli::Commands::Status { json } => {
+            let status = match DaemonClient::connect(&paths) {
+                Some(client) => client.status()?,
+                None => services::status(&paths)?,
+            };
+            report::print_status(&status, *json);
+            Ok(())
+        }
         cli::Commands::Cook { command } => match command {
-            cli::CookCommand::Here { name } => recipe::cook_here(name.clone()),
-            cli::CookCommand::Dispose { name } => recipe::dispose_recipe_cli(name.clone()),
+            cli::CookCommand::Here { name, serve_with, driver } => {
+                let cwd = std::env::current_dir()?;
+                let cooked = match DaemonClient::connect(&paths) {
+                    Some(client) => client.cook(&CookRequest {
+                        path: cwd,
+                        name: name.clone(),
+                        serve_with: serve_with.clone(),
+                        driver: driver.clone(),
+                    })?,
+                    None => {
+                        let opts = recipe::CookOptions {
+                            name: name.clone(),
+                            serve_with: serve_with.clone(),
+                            driver: driver.clone(),
+                        };
+                        recipe::cook(&paths, &cwd, opts)?
+                    }
+                };
+                println!("{} is cooked at {}", cooked.name, cooked.url(&FurnaceConfig::load(&paths)?));
+                Ok(())
+            }
+            cli::CookCommand::Dispose { name } => dispose(&paths, name.clone()),
         },
         cli::Commands::Recipe { command } => match command {
-            cli::RecipeCommand::List => recipe::list_recipes(),
+            cli::RecipeCommand::List => {
+                report::print_recipes(&recipe::get_recipes(&paths)?, &FurnaceConfig::load(&paths)?);
+                Ok(())
+            }
+        },
+        cli::Commands::Assets { command } => match command {
+            cli::AssetsCommand::Extract { force } => {
+                let report = assets::extract(&paths, *force)?;
+                for path in &report.written {
+                    println!("Wrote {}", path.display());
+                }
+                for path in &report.skipped {
+                    println!("Kept existing {}", path.display());
+                }
+                if !report.skipped.is_empty() {
+                    println!("Use --force to overwrite existing files.");
+                }
+                Ok(())
+            }
         },
         cli::Commands::Php { command } => match command {
-            cli::PhpCommand::Install { version } => {
-                if let Err(e) = php::php_install(version) {
-                    eprintln!("Error: {e}");
-                    std::process::exit(1);
+            cli::PhpCommand::Install { version, skip_verify } => {
+                let opts = php::InstallOptions { skip_verify: *skip_verify };
+                let installation = php::php_install(&paths, version, opts)?;
+                println!("Installed PHP {} (php-fpm: {})", installation.version, installation.fpm.display());
+                Ok(())
+            }
+            cli::PhpCommand::Uninstall { version, force } => {
+                let report = match DaemonClient::connect(&paths) {
+                    Some(client) => client.php_uninstall(&PhpUninstallRequest { version: version.clone(), force: *force })?,
+                    None => php::php_uninstall(&paths, version, *force)?,
+                };
+                print_uninstall(&report);
+                Ok(())
+            }
+            cli::PhpCommand::Prune => {
+                let reports = match DaemonClient::connect(&paths) {
+                    Some(client) => client.php_prune()?,
+                    None => php::php_prune(&paths)?,
+                };
+                if reports.is_empty() {
+                    println!("Every installed PHP version is in use.");
+                }
+                for report in &reports {
+                    print_uninstall(report);
+                }
+                Ok(())
+            }
+            cli::PhpCommand::List => {
+                report::print_php_installations(&php::php_list(&paths)?);
+                Ok(())
+            }
+            cli::PhpCommand::Scan => {
+                let report = php_installation::scan(&paths)?;
+                for php in &report.registered {
+                    println!("Registered PHP {} from {}", php.version, php.php.display());
+                }
+                for skip in &report.skipped {
+                    println!("Skipped {}: {}", skip.php.display(), skip.reason);
+                }
+                if report.registered.is_empty() {
+                    println!("No new PHP versions found.");
                 }
+                Ok(())
             }
-            cli::PhpCommand::List => php::php_list(),
+            cli::PhpCommand::UpdateIndex { source } => {
+                let update = php_index::update_index(&paths, source.as_deref())?;
+                if update.not_modified {
+                    println!("PHP index from {} is up to date", update.source);
+                } else {
+                    println!("Updated PHP index from {}", update.source);
+                }
+                println!("Available versions: {}", update.versions.join(", "));
+                Ok(())
+            }
+            cli::PhpCommand::Ini { version, command } => match command {
+                cli::IniCommand::Set { setting } => {
+                    let (key, value) = setting.split_once('=').ok_or_else(|| {
+                        FurnaceError::Config(format!("expected key=value, got '{}'", setting))
+                    })?;
+                    php_ini::ini_set(&paths, version, key, value)?;
+                    println!("Set {} = {} for PHP {}", key.trim(), value.trim(), version);
+                    Ok(())
+                }
+                cli::IniCommand::List => {
+                    for (key, value) in php_ini::ini_settings(&paths, version)? {
+                        println!("{} = {}", key, value);
+                    }
+                    Ok(())
+                }
+            },
+            cli::PhpCommand::Ext { command } => match command {
+                cli::ExtCommand::Enable { version, extension } => {
+                    php_ini::ext_enable(&paths, version, extension)?;
+                    println!("Enabled {} for PHP {}", extension, version);
+                    Ok(())
+                }
+                cli::ExtCommand::Disable { version, extension } => {
+                    php_ini::ext_disable(&paths, version, extension)?;
+                    println!("Disabled {} for PHP {}", extension, version);
+                    Ok(())
+                }
+                cli::ExtCommand::List { version } => {
+                    report::print_extensions(&php_ini::ext_list(&paths, version)?);
+                    Ok(())
+                }
+            },
             cli::PhpCommand::Use { version } => {
-                if let Err(e) = php::php_use(version) {
-                    eprintln!("Error: {e}");
-                    std::process::exit(1);
+                let path = std::env::current_dir()?;
+                match DaemonClient::connect(&paths) {
+                    Some(client) => client.php_use(&PhpUseRequest { path, version: version.clone() })?,
+                    None => php::php_use(&paths, &path, version)?,
                 }
+                println!("Set PHP version {} for project", version);
+                Ok(())
             }
         },
     }
 }
+
+/// A flag set once the process receives Ctrl-C or SIGTERM.
+fn shutdown_flag() -> Result<Arc<AtomicBool>, FurnaceError> {
+    let flag = Arc::new(AtomicBool::new(false));
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    // Register for SIGTERM here so a failure is reported instead of
+    // panicking in the listener thread
+    #[cfg(unix)]
+    let mut term = {
+        let _guard = runtime.enter();
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?
+    };
+    let set = flag.clone();
+    std::thread::spawn(move || {
+        runtime.block_on(async {
+            #[cfg(unix)]
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = term.recv() => {}
+            }
+            #[cfg(not(unix))]
+            let _ = tokio::signal::ctrl_c().await;
+        });
+        println!("Shutting down...");
+        set.store(true, Ordering::SeqCst);
+    });
+    Ok(flag)
+}
+
+fn dispose(paths: &FurnacePaths, name: Option<String>) -> Result<(), FurnaceError> {
+    let target = match name {
+        Some(n) => recipe::RecipeDisposalTarget::ByName(n),
+        None => recipe::RecipeDisposalTarget::ByPath(std::env::current_dir()?),
+    };
+    let disposed = match DaemonClient::connect(paths) {
+        Some(client) => client.dispose(&target)?,
+        None => recipe::dispose(paths, target)?,
+    };
+    for path in &disposed.removed {
+        println!("Removed {}", path.display());
+    }
+    println!("Recipe for '{}' has been disposed.", disposed.name);
+    Ok(())
+}
+
+fn print_uninstall(report: &php::UninstallReport) {
+    for recipe in &report.reassigned {
+        println!("Moved {} to PHP {}", recipe.name, recipe.php_version);
+    }
+    for path in &report.removed {
+        println!("Removed {}", path.display());
+    }
+    println!("PHP {} has been uninstalled.", report.version);
+}
+
+/// The given recipe name, or the recipe cooked from the current directory.
+fn recipe_name(paths: &FurnacePaths, name: Option<String>) -> Result<String, FurnaceError> {
+    match name {
+        Some(n) => Ok(n),
+        None => Ok(recipe::recipe_for_dir(paths, &std::env::current_dir()?)?.name),
+    }
+}
+
+/// Map core errors to sysexits-style process exit codes.
+fn exit_code(err: &FurnaceError) -> i32 {
+    match err.kind() {
+        ErrorKind::Invalid => 65,
+        ErrorKind::NotFound => 66,
+        ErrorKind::Unavailable => 69,
+        ErrorKind::Internal => 70,
+        ErrorKind::Io => 74,
+        ErrorKind::Conflict => 75,
+        ErrorKind::Config => 78,
+    }
+}
@@ -0,0 +1,126 @@
+use furnace_core::config::FurnaceConfig;
+use furnace_core::php_ini::ExtensionStatus;
+use furnace_core::php_installation::PhpInstallation;
+use furnace_core::recipe::Recipe;
+use furnace_core::status::FurnaceStatus;
+use prettytable::{row, Table};
+
+/// Print a status snapshot either as JSON or as human-readable tables.
+pub fn print_status(status: &FurnaceStatus, json: bool) {
+    if json {
+        match serde_json::to_string_pretty(status) {
+            Ok(s) => println!("{}", s),
+            Err(e) => eprintln!("Failed to serialize status: {e}"),
+        }
+        return;
+    }
+
+    let mut services = Table::new();
+    services.add_row(row!["Service", "State", "PID", "Details"]);
+    services.add_row(row![
+        "nginx",
+        status.nginx.state,
+        pid_cell(status.nginx.pid),
+        status.nginx.pid_file
+    ]);
+    services.add_row(row![
+        "apache",
+        status.apache.state,
+        pid_cell(status.apache.pid),
+        status.apache.pid_file
+    ]);
+    for pool in &status.php_pools {
+        let details = if pool.responding {
+            "socket responding".to_string()
+        } else if pool.socket_exists {
+            "socket present, not responding".to_string()
+        } else {
+            "no socket".to_string()
+        };
+        services.add_row(row![
+            format!("php-fpm {}", pool.version),
+            pool.process.state,
+            pid_cell(pool.process.pid),
+            details
+        ]);
+    }
+    let dns_state = if status.dns.configured { "configured" } else { "not configured" };
+    services.add_row(row![
+        format!("dns ({})", status.dns.provider),
+        dns_state,
+        "-",
+        status.dns.details
+    ]);
+    if let Some(server) = &status.dns.server {
+        services.add_row(row!["dnsmasq", server.state, pid_cell(server.pid), server.pid_file]);
+    }
+    services.printstd();
+
+    if status.recipes.is_empty() {
+        println!("No recipes cooked yet.");
+        return;
+    }
+    let mut recipes = Table::new();
+    recipes.add_row(row!["Recipe", "Site", "PHP", "Serve With", "Health"]);
+    for recipe in &status.recipes {
+        let health = if recipe.healthy {
+            "ok".to_string()
+        } else {
+            recipe.issues.join("\n")
+        };
+        recipes.add_row(row![
+            recipe.name,
+            recipe.site,
+            recipe.php_version,
+            recipe.serve_with,
+            health
+        ]);
+    }
+    recipes.printstd();
+}
+
+/// Print registered recipes as a table.
+pub fn print_recipes(recipes: &[Recipe], config: &FurnaceConfig) {
+    let mut table = Table::new();
+    table.add_row(row!["Name", "Directory", "URL"]);
+    for recipe in recipes {
+        table.add_row(row![recipe.name, recipe.path, recipe.url(config)]);
+    }
+    table.printstd();
+}
+
+/// Print installed PHP versions as a table.
+pub fn print_php_installations(installations: &[PhpInstallation]) {
+    if installations.is_empty() {
+        println!("No PHP versions installed yet.");
+        return;
+    }
+    let mut table = Table::new();
+    table.add_row(row!["Version", "Kind", "PHP", "FPM", "php.ini"]);
+    for php in installations {
+        let ini = php.php_ini.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "-".to_string());
+        table.add_row(row![php.version, php.kind, php.php.display(), php.fpm.display(), ini]);
+    }
+    table.printstd();
+}
+
+pub fn print_extensions(extensions: &[ExtensionStatus]) {
+    if extensions.is_empty() {
+        println!("No shared extensions found.");
+        return;
+    }
+    let mut table = Table::new();
+    table.add_row(row!["Extension", "Enabled", "Available"]);
+    for ext in extensions {
+        table.add_row(row![ext.name, yes_no(ext.enabled), yes_no(ext.available)]);
+    }
+    table.printstd();
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+fn pid_cell(pid: Option<u32>) -> String {
+    pid.map(|p| p.to_string()).unwrap_or_else(|| "-".to_string())
+}
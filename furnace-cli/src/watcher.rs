@@ -0,0 +1,226 @@
+//! Watches every recipe's project and the templates under `~/.furnace`,
+//! keeping generated config in step with them.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use furnace_core::recipe::{self, Recipe};
+use furnace_core::web_service::{NginxService, WebService};
+use furnace_core::{php, php_installation, services, FurnaceError, FurnacePaths};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{error, info, warn};
+
+/// Editors save in bursts; events this close together are handled once.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+/// How often project directories are checked for having gone missing.
+const ORPHAN_CHECK: Duration = Duration::from_secs(5);
+
+/// What a batch of events asks for.
+#[derive(Default)]
+struct Changes {
+    templates: bool,
+    fpm_templates: bool,
+    recipes: bool,
+    orphans: bool,
+    refresh: BTreeSet<String>,
+}
+
+struct State<'a> {
+    paths: &'a FurnacePaths,
+    watcher: RecommendedWatcher,
+    watched: Vec<PathBuf>,
+    recipes: Vec<Recipe>,
+    /// `require.php` last seen in each recipe's composer.json
+    requirements: HashMap<String, Option<String>>,
+}
+
+/// Watches until `shutdown` is set.
+pub fn watch(paths: &FurnacePaths, shutdown: &AtomicBool) -> Result<(), FurnaceError> {
+    let (tx, rx) = mpsc::channel();
+    let watcher = notify::recommended_watcher(tx).map_err(watch_error)?;
+    let mut state = State {
+        paths,
+        watcher,
+        watched: Vec::new(),
+        recipes: Vec::new(),
+        requirements: HashMap::new(),
+    };
+    state.rewatch()?;
+    state.check_orphans();
+    info!("Watching {} recipes for changes", state.recipes.len());
+
+    let mut last_check = Instant::now();
+    while !shutdown.load(Ordering::SeqCst) {
+        match rx.recv_timeout(Duration::from_secs(1)) {
+            Ok(first) => {
+                std::thread::sleep(DEBOUNCE);
+                let events: Vec<_> = std::iter::once(first).chain(rx.try_iter()).collect();
+                let changes = state.classify(events);
+                // Keep watching; the next edit may fix whatever went wrong
+                if let Err(e) = state.apply(changes) {
+                    error!("Failed to apply file changes: {e}");
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if last_check.elapsed() >= ORPHAN_CHECK {
+            state.check_orphans();
+            last_check = Instant::now();
+        }
+    }
+    Ok(())
+}
+
+fn watch_error(e: notify::Error) -> FurnaceError {
+    FurnaceError::Io(std::io::Error::other(e))
+}
+
+fn project_templates(recipe: &Recipe) -> PathBuf {
+    Path::new(&recipe.path).join(".furnace/templates")
+}
+
+impl State<'_> {
+    /// Reloads the recipe list and watches the directories it needs.
+    fn rewatch(&mut self) -> Result<(), FurnaceError> {
+        for path in self.watched.drain(..) {
+            let _ = self.watcher.unwatch(&path);
+        }
+        self.recipes = recipe::get_recipes(self.paths)?;
+        std::fs::create_dir_all(self.paths.templates_dir())?;
+        std::fs::create_dir_all(self.paths.recipes_dir())?;
+        let mut targets = vec![
+            (self.paths.templates_dir(), RecursiveMode::Recursive),
+            (self.paths.recipes_dir(), RecursiveMode::NonRecursive),
+        ];
+        for recipe in &self.recipes {
+            let dir = PathBuf::from(&recipe.path);
+            if dir.is_dir() {
+                targets.push((dir, RecursiveMode::NonRecursive));
+            }
+            if project_templates(recipe).is_dir() {
+                targets.push((project_templates(recipe), RecursiveMode::Recursive));
+            }
+            self.requirements
+                .entry(recipe.name.clone())
+                .or_insert_with(|| recipe::parse_php_version(Path::new(&recipe.path).join("composer.json")));
+        }
+        for (path, mode) in targets {
+            match self.watcher.watch(&path, mode) {
+                Ok(()) => self.watched.push(path),
+                Err(e) => warn!("Cannot watch {}: {e}", path.display()),
+            }
+        }
+        Ok(())
+    }
+
+    fn classify(&mut self, events: Vec<notify::Result<Event>>) -> Changes {
+        let mut changes = Changes::default();
+        let templates_dir = self.paths.templates_dir();
+        let recipes_dir = self.paths.recipes_dir();
+        for event in events {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!("File watch error: {e}");
+                    continue;
+                }
+            };
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+            for path in &event.paths {
+                if path.starts_with(&templates_dir) {
+                    changes.templates = true;
+                    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                    changes.fpm_templates |= name.starts_with("php-fpm");
+                    continue;
+                }
+                if path.starts_with(&recipes_dir) {
+                    changes.recipes = true;
+                    continue;
+                }
+                for recipe in &self.recipes {
+                    let dir = Path::new(&recipe.path);
+                    if path == dir {
+                        changes.orphans = true;
+                    } else if path == &dir.join(".furnace.yml") || path.starts_with(project_templates(recipe)) {
+                        changes.refresh.insert(recipe.name.clone());
+                    } else if path == &dir.join("composer.json") {
+                        // Only a new require.php matters, not every composer update
+                        let requirement = recipe::parse_php_version(path);
+                        if self.requirements.get(&recipe.name) != Some(&requirement) {
+                            self.requirements.insert(recipe.name.clone(), requirement);
+                            changes.refresh.insert(recipe.name.clone());
+                        }
+                    }
+                }
+            }
+        }
+        changes
+    }
+
+    fn apply(&mut self, changes: Changes) -> Result<(), FurnaceError> {
+        for name in &changes.refresh {
+            match recipe::refresh(self.paths, name) {
+                Ok(recipe) => info!("Refreshed {} (PHP {})", recipe.name, recipe.php_version),
+                Err(e) => error!("Failed to refresh {}: {e}", name),
+            }
+        }
+        if changes.fpm_templates {
+            for installation in php_installation::load_manifest(self.paths)?.values() {
+                let regenerated = php::php_fpm_conf(self.paths, &installation.version)
+                    .and_then(|_| services::restart_php_fpm(self.paths, installation));
+                if let Err(e) = regenerated {
+                    error!("Failed to apply PHP-FPM template for {}: {e}", installation.version);
+                }
+            }
+        }
+        if changes.templates {
+            info!("Templates changed; testing and reloading web servers");
+            if NginxService::new(self.paths).is_running() {
+                NginxService::new(self.paths).write_main_conf()?;
+            }
+            if let Err(e) = services::reload(self.paths) {
+                error!("Template change not applied: {e}");
+            }
+        }
+        if changes.orphans {
+            self.check_orphans();
+        }
+        if changes.recipes || !changes.refresh.is_empty() {
+            self.rewatch()?;
+        }
+        Ok(())
+    }
+
+    /// Flags recipes whose project directory is gone, and brings back those
+    /// whose directory reappeared.
+    fn check_orphans(&mut self) {
+        let mut changed = false;
+        for recipe in &self.recipes {
+            let exists = Path::new(&recipe.path).is_dir();
+            if !exists && !recipe.orphaned {
+                warn!("{} is orphaned: {} was moved or deleted", recipe.name, recipe.path);
+                if let Err(e) = recipe::set_orphaned(self.paths, &recipe.name, true) {
+                    error!("Failed to flag {} as orphaned: {e}", recipe.name);
+                }
+                changed = true;
+            } else if exists && recipe.orphaned {
+                info!("{} is back at {}", recipe.name, recipe.path);
+                if let Err(e) = recipe::refresh(self.paths, &recipe.name) {
+                    error!("Failed to refresh {}: {e}", recipe.name);
+                }
+                changed = true;
+            }
+        }
+        if changed {
+            if let Err(e) = self.rewatch() {
+                error!("Failed to update watches: {e}");
+            }
+        }
+    }
+}
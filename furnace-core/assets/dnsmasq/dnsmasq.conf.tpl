@@ -0,0 +1,12 @@
+# Answers every *.{tld} lookup with this machine and nothing else. Runs as
+# the current user on an unprivileged port, loopback only.
+port={port}
+listen-address=127.0.0.1
+bind-interfaces
+no-resolv
+no-hosts
+no-poll
+local=/{tld}/
+address=/{tld}/127.0.0.1
+pid-file={pid_file}
+log-facility={log_file}
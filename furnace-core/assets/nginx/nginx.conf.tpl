@@ -2,13 +2,15 @@
 # This file is generated and managed by Furnace.
 
 worker_processes  1;
+pid {pid_file};
+error_log {logs_dir}/error.log;
 
 events {
     worker_connections  1024;
 }
 
 http {
-    include /opt/homebrew/etc/nginx/mime.types;
+    {mime_types}
     default_type  application/octet-stream;
     sendfile        on;
     keepalive_timeout  65;
@@ -0,0 +1,15 @@
+[furnace-{name}]
+user = {user}
+group = {group}
+listen = {sock_path}
+listen.owner = {user}
+listen.group = {group}
+listen.mode = 0660
+pm = {pm}
+pm.max_children = {max_children}
+pm.start_servers = {start_servers}
+pm.min_spare_servers = {min_spare_servers}
+pm.max_spare_servers = {max_spare_servers}
+chdir = /
+env[PHP_INI_SCAN_DIR] = {ini_scan_dir}
+{settings}
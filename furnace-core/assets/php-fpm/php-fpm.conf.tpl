@@ -1,5 +1,4 @@
 [global]
-include=NONE
 pid = {php_dir}/php-fpm.pid
 error_log = {php_dir}/php-fpm.log
 
@@ -16,3 +15,8 @@ pm.start_servers = 2
 pm.min_spare_servers = 1
 pm.max_spare_servers = 3
 chdir = /
+; Lets PHP spawned by the app load the same conf.d as the pool
+env[PHP_INI_SCAN_DIR] = {ini_scan_dir}
+
+; Per-project pools; must come last since it opens new sections
+include={pool_dir}/*.conf
@@ -0,0 +1,23 @@
+
+<VirtualHost *:{https_port}>
+    ServerName {site}
+    DocumentRoot "{root}"
+    DirectoryIndex {index}
+
+    SSLEngine on
+    SSLCertificateFile "{cert}"
+    SSLCertificateKeyFile "{cert_key}"
+
+    <Directory "{root}">
+        AllowOverride All
+        Require all granted
+        {fallback}
+    </Directory>
+
+    <FilesMatch \.php$>
+        SetHandler "proxy:unix:{socket}|fcgi://localhost/"
+    </FilesMatch>
+
+    ErrorLog "{logs_dir}/{name}.error.log"
+    CustomLog "{logs_dir}/{name}.access.log" combined
+</VirtualHost>
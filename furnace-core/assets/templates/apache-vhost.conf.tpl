@@ -0,0 +1,18 @@
+<VirtualHost *:{port}>
+    ServerName {site}
+    DocumentRoot "{root}"
+    DirectoryIndex {index}
+
+    <Directory "{root}">
+        AllowOverride All
+        Require all granted
+        {fallback}
+    </Directory>
+
+    <FilesMatch \.php$>
+        SetHandler "proxy:unix:{socket}|fcgi://localhost/"
+    </FilesMatch>
+
+    ErrorLog "{logs_dir}/{name}.error.log"
+    CustomLog "{logs_dir}/{name}.access.log" combined
+</VirtualHost>
@@ -0,0 +1,27 @@
+
+server {
+    listen {https_port} ssl;
+    server_name {site};
+    root {root};
+
+    ssl_certificate {cert};
+    ssl_certificate_key {cert_key};
+
+    index {index};
+
+    access_log {logs_dir}/{name}.access.log;
+    error_log {logs_dir}/{name}.error.log;
+
+    location / {
+        try_files {try_files};
+    }
+
+{locations}
+    location ~ \.php$ {
+        include {fastcgi_params};
+        fastcgi_pass unix:{socket};
+        fastcgi_param SCRIPT_FILENAME $document_root$fastcgi_script_name;
+        fastcgi_param HTTPS on;
+        fastcgi_index index.php;
+    }
+}
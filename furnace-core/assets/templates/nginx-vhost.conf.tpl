@@ -0,0 +1,22 @@
+server {
+    listen {port};
+    server_name {site};
+    root {root};
+
+    index {index};
+
+    access_log {logs_dir}/{name}.access.log;
+    error_log {logs_dir}/{name}.error.log;
+
+    location / {
+        try_files {try_files};
+    }
+
+{locations}
+    location ~ \.php$ {
+        include {fastcgi_params};
+        fastcgi_pass unix:{socket};
+        fastcgi_param SCRIPT_FILENAME $document_root$fastcgi_script_name;
+        fastcgi_index index.php;
+    }
+}
@@ -0,0 +1,61 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::paths::FurnacePaths;
+use crate::php::BUNDLED_REPOSITORY;
+use crate::template::Template;
+
+/// Result of [`extract`].
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ExtractReport {
+    pub written: Vec<PathBuf>,
+    /// User copies left untouched because they already exist
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Writes every bundled default where Furnace looks for user copies, so
+/// they can be customized. Existing files are kept unless `force` is set.
+pub fn extract(paths: &FurnacePaths, force: bool) -> Result<ExtractReport> {
+    let mut assets: Vec<(PathBuf, &str)> = Template::ALL
+        .iter()
+        .map(|t| (paths.templates_dir().join(t.file_name()), t.bundled()))
+        .collect();
+    // Not repository.yml: that is an override layer, and a verbatim copy
+    // would shadow every fetched index
+    assets.push((paths.repository_example_file(), BUNDLED_REPOSITORY));
+
+    let mut report = ExtractReport::default();
+    fs::create_dir_all(paths.templates_dir())?;
+    for (path, contents) in assets {
+        if path.exists() && !force {
+            report.skipped.push(path);
+        } else {
+            fs::write(&path, contents)?;
+            report.written.push(path);
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template;
+
+    #[test]
+    fn extracted_copies_take_precedence() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path());
+        let report = extract(&paths, false).unwrap();
+        assert_eq!(report.written.len(), Template::ALL.len() + 1);
+        assert!(!paths.repository_file().exists());
+
+        let conf = paths.templates_dir().join(Template::NginxConf.file_name());
+        fs::write(&conf, "custom").unwrap();
+        assert_eq!(extract(&paths, false).unwrap().skipped.len(), report.written.len());
+        assert_eq!(template::source(&paths, None, Template::NginxConf).unwrap(), "custom");
+    }
+}
@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dns::DnsProviderKind;
+use crate::error::Result;
+use crate::paths::FurnacePaths;
+
+/// Global settings read from `~/.furnace/config.yml`. Missing keys, or a
+/// missing file, fall back to the defaults.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct FurnaceConfig {
+    /// Port vhosts listen on for HTTP
+    pub http_port: u16,
+    /// Port vhosts listen on for HTTPS
+    pub https_port: u16,
+    /// Where `furnace php update-index` fetches the PHP index from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub php_index_url: Option<String>,
+    /// How `.test` sites resolve: dnsmasq, resolved or hosts
+    pub dns: DnsProviderKind,
+    /// Port Furnace's dnsmasq listens on
+    pub dns_port: u16,
+    /// Hosts file the hosts provider manages; defaults to the system's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hosts_file: Option<PathBuf>,
+}
+
+impl Default for FurnaceConfig {
+    fn default() -> Self {
+        FurnaceConfig {
+            http_port: 80,
+            https_port: 443,
+            php_index_url: None,
+            dns: DnsProviderKind::default(),
+            dns_port: 53535,
+            hosts_file: None,
+        }
+    }
+}
+
+impl FurnaceConfig {
+    pub fn load(paths: &FurnacePaths) -> Result<Self> {
+        match fs::read_to_string(paths.config_file()) {
+            Ok(content) if !content.trim().is_empty() => Ok(serde_yaml::from_str(&content)?),
+            Ok(_) => Ok(Self::default()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Site address, with the port only when it isn't the scheme's default.
+    pub fn site_url(&self, site: &str, secure: bool) -> String {
+        let (scheme, port, default_port) = if secure {
+            ("https", self.https_port, 443)
+        } else {
+            ("http", self.http_port, 80)
+        };
+        if port == default_port {
+            format!("{}://{}", scheme, site)
+        } else {
+            format!("{}://{}:{}", scheme, site, port)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_config_keeps_defaults() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path());
+        assert_eq!(FurnaceConfig::load(&paths).unwrap(), FurnaceConfig::default());
+
+        fs::write(paths.config_file(), "http_port: 8080\n").unwrap();
+        let config = FurnaceConfig::load(&paths).unwrap();
+        assert_eq!(config.https_port, 443);
+        assert_eq!(config.site_url("shop.test", false), "http://shop.test:8080");
+        assert_eq!(config.site_url("shop.test", true), "https://shop.test");
+    }
+}
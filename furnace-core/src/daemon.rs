@@ -0,0 +1,212 @@
+//! Client side of the `furnace daemon` control API, shared by the CLI and
+//! the desktop app. The daemon listens on 127.0.0.1 and records its port and
+//! a bearer token in `~/.furnace/daemon.json`.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ErrorKind, FurnaceError, Result};
+use crate::paths::FurnacePaths;
+use crate::php::UninstallReport;
+use crate::php_installation;
+use crate::process::{self, StopResult};
+use crate::recipe::{DisposeReport, Recipe, RecipeDisposalTarget};
+use crate::status::{self, FurnaceStatus};
+
+/// Contents of `~/.furnace/daemon.json`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DaemonInfo {
+    pub pid: u32,
+    pub port: u16,
+    pub token: String,
+}
+
+impl DaemonInfo {
+    /// Records the daemon's address, readable only by the current user.
+    pub fn write(&self, paths: &FurnacePaths) -> Result<()> {
+        fs::create_dir_all(paths.root())?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // Created private so the token is never readable by others
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(paths.daemon_file())?;
+        #[cfg(unix)]
+        {
+            // `mode` only applies to new files; tighten one left by an older release
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        std::io::Write::write_all(&mut file, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// The recorded daemon, if its process is still alive.
+    pub fn load(paths: &FurnacePaths) -> Option<DaemonInfo> {
+        let content = fs::read_to_string(paths.daemon_file()).ok()?;
+        let info: DaemonInfo = serde_json::from_str(&content).ok()?;
+        status::process_alive(info.pid).then_some(info)
+    }
+
+    pub fn remove(paths: &FurnacePaths) -> Result<()> {
+        match fs::remove_file(paths.daemon_file()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CookRequest {
+    pub path: PathBuf,
+    pub name: Option<String>,
+    pub serve_with: Option<String>,
+    pub driver: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhpUseRequest {
+    /// Project directory
+    pub path: PathBuf,
+    pub version: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhpUninstallRequest {
+    pub version: String,
+    /// Move recipes still using the version to another one
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StartRequest {
+    /// Kill processes holding the HTTP/HTTPS ports
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LogsResponse {
+    pub service: String,
+    pub lines: Vec<String>,
+}
+
+/// Body of every non-2xx response.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ErrorResponse {
+    pub error: String,
+    #[serde(default)]
+    pub kind: ErrorKind,
+}
+
+/// Blocking client for a running daemon.
+pub struct DaemonClient {
+    base: String,
+    token: String,
+    http: Client,
+    paths: FurnacePaths,
+}
+
+impl DaemonClient {
+    /// Connects to the daemon recorded in `paths`, if one is running.
+    pub fn connect(paths: &FurnacePaths) -> Option<DaemonClient> {
+        let info = DaemonInfo::load(paths)?;
+        Some(DaemonClient {
+            base: format!("http://127.0.0.1:{}", info.port),
+            token: info.token,
+            http: Client::new(),
+            paths: paths.clone(),
+        })
+    }
+
+    pub fn status(&self) -> Result<FurnaceStatus> {
+        self.send(self.http.get(self.url("/status")))
+    }
+
+    pub fn cook(&self, request: &CookRequest) -> Result<Recipe> {
+        self.send(self.http.post(self.url("/recipes/cook")).json(request))
+    }
+
+    pub fn dispose(&self, target: &RecipeDisposalTarget) -> Result<DisposeReport> {
+        self.send(self.http.post(self.url("/recipes/dispose")).json(target))
+    }
+
+    pub fn php_use(&self, request: &PhpUseRequest) -> Result<()> {
+        self.send(self.http.post(self.url("/php/use")).json(request))
+    }
+
+    pub fn php_uninstall(&self, request: &PhpUninstallRequest) -> Result<UninstallReport> {
+        let request = self.http.post(self.url("/php/uninstall")).json(request);
+        self.send(request.timeout(stop_timeout(1)))
+    }
+
+    pub fn php_prune(&self) -> Result<Vec<UninstallReport>> {
+        let request = self.http.post(self.url("/php/prune")).json(&());
+        self.send(request.timeout(stop_timeout(self.php_versions())))
+    }
+
+    pub fn start(&self, force: bool) -> Result<()> {
+        // Starting first stops anything Furnace left running unsupervised
+        let request = self.http.post(self.url("/services/start")).json(&StartRequest { force });
+        self.send(request.timeout(stop_timeout(SERVICES + self.php_versions())))
+    }
+
+    pub fn stop(&self) -> Result<Vec<StopResult>> {
+        let request = self.http.post(self.url("/services/stop")).json(&());
+        self.send(request.timeout(stop_timeout(SERVICES + self.php_versions())))
+    }
+
+    pub fn restart(&self, force: bool) -> Result<()> {
+        let request = self.http.post(self.url("/services/restart")).json(&StartRequest { force });
+        self.send(request.timeout(stop_timeout(SERVICES + self.php_versions())))
+    }
+
+    pub fn logs(&self, service: &str, lines: usize) -> Result<LogsResponse> {
+        let request = self
+            .http
+            .get(self.url(&format!("/logs/{}", service)))
+            .query(&[("lines", lines)]);
+        self.send(request)
+    }
+
+    fn php_versions(&self) -> usize {
+        php_installation::load_manifest(&self.paths).map_or(0, |m| m.len())
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base, path)
+    }
+
+    fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let response = request
+            .bearer_auth(&self.token)
+            .send()
+            .map_err(|e| FurnaceError::Daemon(e.to_string()))?;
+        if response.status().is_success() {
+            return response.json().map_err(|e| FurnaceError::Daemon(e.to_string()));
+        }
+        let status = response.status();
+        match response.json::<ErrorResponse>() {
+            Ok(e) => Err(FurnaceError::Remote { kind: e.kind, message: e.error }),
+            Err(_) => Err(FurnaceError::Daemon(status.to_string())),
+        }
+    }
+}
+
+/// nginx, Apache and the DNS server, stopped alongside every FPM master.
+const SERVICES: usize = 3;
+
+/// Slack on top of the worst-case stop time for the rest of a request,
+/// such as starting services again.
+const REQUEST_MARGIN: Duration = Duration::from_secs(30);
+
+/// How long a request that stops `processes` one after another may take.
+fn stop_timeout(processes: usize) -> Duration {
+    process::MAX_STOP_TIME * processes as u32 + REQUEST_MARGIN
+}
@@ -0,0 +1,468 @@
+//! Makes recipe sites under `.test` resolve to this machine. `dns` in
+//! config.yml picks the [`DnsProvider`]; none of them need sudo or brew.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::config::FurnaceConfig;
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+use crate::process::{self, Signal, StopResult};
+use crate::recipe;
+use crate::status::{self, ProcessStatus};
+use crate::supervisor::{self, ProcessSpec};
+use crate::template::{self, Template};
+
+/// Top-level domain recipe sites live under.
+pub const TLD: &str = "test";
+
+const HOSTS_BEGIN: &str = "# BEGIN furnace";
+const HOSTS_END: &str = "# END furnace";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DnsProviderKind {
+    /// Furnace's own dnsmasq on a high port; on macOS `/etc/resolver/test`
+    /// points the system at it
+    Dnsmasq,
+    /// Furnace's dnsmasq, routed to through a systemd-resolved drop-in
+    Resolved,
+    /// A managed block in the hosts file listing every recipe's site
+    Hosts,
+}
+
+impl Default for DnsProviderKind {
+    fn default() -> Self {
+        // Resolved needs an administrator once; the hosts file on every cook
+        if cfg!(target_os = "linux") {
+            DnsProviderKind::Resolved
+        } else if cfg!(windows) {
+            DnsProviderKind::Hosts
+        } else {
+            DnsProviderKind::Dnsmasq
+        }
+    }
+}
+
+impl std::fmt::Display for DnsProviderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            DnsProviderKind::Dnsmasq => "dnsmasq",
+            DnsProviderKind::Resolved => "resolved",
+            DnsProviderKind::Hosts => "hosts",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DnsStatus {
+    pub provider: DnsProviderKind,
+    /// Furnace's dnsmasq, for providers that run one
+    pub server: Option<ProcessStatus>,
+    /// Whether the system sends `.test` lookups to Furnace
+    pub configured: bool,
+    pub details: String,
+}
+
+/// One way of resolving `.test` sites.
+pub trait DnsProvider {
+    fn kind(&self) -> DnsProviderKind;
+    /// The DNS server this provider needs running, with its config written.
+    fn server(&self) -> Result<Option<ProcessSpec>> {
+        Ok(None)
+    }
+    /// Points the system resolver at Furnace; called when services start.
+    fn configure(&self) -> Result<()>;
+    /// Undoes [`DnsProvider::configure`] where that needs no extra rights.
+    fn unconfigure(&self) -> Result<()> {
+        Ok(())
+    }
+    /// Called whenever recipes are cooked or disposed.
+    fn sync_sites(&self, sites: &[String]) -> Result<()> {
+        let _ = sites;
+        Ok(())
+    }
+    fn status(&self) -> DnsStatus;
+}
+
+/// The provider selected in config.yml.
+pub fn provider(paths: &FurnacePaths) -> Result<Box<dyn DnsProvider>> {
+    let config = FurnaceConfig::load(paths)?;
+    let dnsmasq = Dnsmasq { paths: paths.clone(), port: config.dns_port };
+    Ok(match config.dns {
+        DnsProviderKind::Dnsmasq => Box::new(dnsmasq),
+        DnsProviderKind::Resolved => Box::new(Resolved {
+            dnsmasq,
+            drop_in: PathBuf::from("/etc/systemd/resolved.conf.d/furnace.conf"),
+        }),
+        DnsProviderKind::Hosts => Box::new(Hosts {
+            file: config.hosts_file.unwrap_or_else(system_hosts_file),
+        }),
+    })
+}
+
+/// Starts the provider's DNS server in the background, if it has one, and
+/// configures the system to use it.
+pub fn start(paths: &FurnacePaths) -> Result<()> {
+    let provider = provider(paths)?;
+    if let Some(spec) = provider.server()? {
+        match supervisor::clean_stale(&spec) {
+            Err(FurnaceError::AlreadyRunning(_)) => info!("{} already running", spec.name),
+            result => {
+                result?;
+                let child = supervisor::start(&spec)?;
+                info!("Started {} (PID {})", spec.name, child.id());
+            }
+        }
+    }
+    configure(paths, provider.as_ref())
+}
+
+/// Configures the system for a provider whose server a supervisor runs.
+pub fn configure(paths: &FurnacePaths, provider: &dyn DnsProvider) -> Result<()> {
+    provider.configure()?;
+    provider.sync_sites(&sites(paths)?)
+}
+
+/// Stops the provider's DNS server, then undoes the system configuration.
+/// A failure to undo it only warns, so the server is still reported.
+pub fn stop(paths: &FurnacePaths) -> Result<Option<StopResult>> {
+    let provider = provider(paths)?;
+    let stopped = provider
+        .server()?
+        .map(|spec| process::stop(&spec.name, &spec.pid_file, spec.stop_signal, &spec.sockets));
+    if let Err(e) = provider.unconfigure() {
+        warn!("Could not undo .{} DNS setup: {e}", TLD);
+    }
+    Ok(stopped)
+}
+
+/// Brings the provider in line with the current recipes.
+pub fn sync(paths: &FurnacePaths) -> Result<()> {
+    provider(paths)?.sync_sites(&sites(paths)?)
+}
+
+pub fn status(paths: &FurnacePaths) -> Result<DnsStatus> {
+    Ok(provider(paths)?.status())
+}
+
+fn sites(paths: &FurnacePaths) -> Result<Vec<String>> {
+    Ok(recipe::get_recipes(paths)?.into_iter().map(|r| r.site).collect())
+}
+
+/// Runs dnsmasq as the current user, answering only for `.test`.
+struct Dnsmasq {
+    paths: FurnacePaths,
+    port: u16,
+}
+
+impl Dnsmasq {
+    fn write_conf(&self) -> Result<PathBuf> {
+        let vars = [
+            ("tld", TLD.to_string()),
+            ("port", self.port.to_string()),
+            ("pid_file", self.paths.dnsmasq_pid().to_string_lossy().to_string()),
+            ("log_file", self.paths.dnsmasq_log().to_string_lossy().to_string()),
+        ];
+        let conf = template::render_str(&template::source(&self.paths, None, Template::DnsmasqConf)?, &vars);
+        let path = self.paths.dnsmasq_conf();
+        fs::create_dir_all(self.paths.dnsmasq_dir())?;
+        fs::write(&path, conf)?;
+        Ok(path)
+    }
+
+    fn spec(&self) -> Result<ProcessSpec> {
+        let conf = self.write_conf()?;
+        Ok(ProcessSpec {
+            name: "dnsmasq".to_string(),
+            program: PathBuf::from("dnsmasq"),
+            args: vec!["--keep-in-foreground".into(), format!("--conf-file={}", conf.display()).into()],
+            env: Vec::new(),
+            pid_file: self.paths.dnsmasq_pid(),
+            sockets: Vec::new(),
+            stop_signal: Signal::Term,
+        })
+    }
+
+    fn server_status(&self) -> ProcessStatus {
+        status::process_status(&self.paths.dnsmasq_pid())
+    }
+
+    /// macOS sends lookups for a domain to the nameserver in
+    /// `/etc/resolver/<domain>`, port included.
+    fn resolver_file(&self) -> Option<PathBuf> {
+        cfg!(target_os = "macos").then(|| Path::new("/etc/resolver").join(TLD))
+    }
+
+    fn resolver_conf(&self) -> String {
+        format!("nameserver 127.0.0.1\nport {}\n", self.port)
+    }
+}
+
+impl DnsProvider for Dnsmasq {
+    fn kind(&self) -> DnsProviderKind {
+        DnsProviderKind::Dnsmasq
+    }
+
+    fn server(&self) -> Result<Option<ProcessSpec>> {
+        self.spec().map(Some)
+    }
+
+    fn configure(&self) -> Result<()> {
+        let Some(file) = self.resolver_file() else {
+            warn!(
+                "Forward .{} lookups to 127.0.0.1#{} in your resolver, or set `dns: resolved` or `dns: hosts` in config.yml",
+                TLD, self.port
+            );
+            return Ok(());
+        };
+        if fs::read_to_string(&file).is_ok_and(|c| c == self.resolver_conf()) {
+            return Ok(());
+        }
+        if fs::create_dir_all("/etc/resolver").and_then(|_| fs::write(&file, self.resolver_conf())).is_err() {
+            warn!(
+                "Could not write {}. Create it once as an administrator with:\n{}",
+                file.display(),
+                self.resolver_conf()
+            );
+        }
+        Ok(())
+    }
+
+    fn status(&self) -> DnsStatus {
+        let configured = self
+            .resolver_file()
+            .is_some_and(|f| fs::read_to_string(f).is_ok_and(|c| c == self.resolver_conf()));
+        DnsStatus {
+            provider: self.kind(),
+            server: Some(self.server_status()),
+            configured,
+            details: format!("dnsmasq on 127.0.0.1#{}", self.port),
+        }
+    }
+}
+
+/// Furnace's dnsmasq behind a systemd-resolved drop-in that routes only
+/// `~test` lookups to it. The uplink's own DNS servers are left alone.
+struct Resolved {
+    dnsmasq: Dnsmasq,
+    drop_in: PathBuf,
+}
+
+impl Resolved {
+    fn drop_in_conf(&self) -> String {
+        format!("[Resolve]\nDNS=127.0.0.1:{}\nDomains=~{}\n", self.dnsmasq.port, TLD)
+    }
+
+    fn reload(&self) {
+        let reloaded = Command::new("systemctl")
+            .args(["--no-ask-password", "try-reload-or-restart", "systemd-resolved"])
+            .output();
+        match reloaded {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => {
+                let command = "systemctl try-reload-or-restart systemd-resolved";
+                warn!("{}", FurnaceError::command_failed(command, &output));
+            }
+            Err(e) => warn!("Could not reload systemd-resolved: {e}"),
+        }
+    }
+}
+
+impl DnsProvider for Resolved {
+    fn kind(&self) -> DnsProviderKind {
+        DnsProviderKind::Resolved
+    }
+
+    fn server(&self) -> Result<Option<ProcessSpec>> {
+        self.dnsmasq.server()
+    }
+
+    fn configure(&self) -> Result<()> {
+        if fs::read_to_string(&self.drop_in).is_ok_and(|c| c == self.drop_in_conf()) {
+            return Ok(());
+        }
+        let parent = self.drop_in.parent().unwrap_or(Path::new("/"));
+        if fs::create_dir_all(parent).and_then(|_| fs::write(&self.drop_in, self.drop_in_conf())).is_err() {
+            warn!(
+                "Could not write {}. Create it once as an administrator, then reload systemd-resolved, with:\n{}",
+                self.drop_in.display(),
+                self.drop_in_conf()
+            );
+            return Ok(());
+        }
+        info!("Routed .{} lookups to 127.0.0.1:{} in {}", TLD, self.dnsmasq.port, self.drop_in.display());
+        self.reload();
+        Ok(())
+    }
+
+    fn unconfigure(&self) -> Result<()> {
+        match fs::remove_file(&self.drop_in) {
+            Ok(()) => self.reload(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Could not remove {}: {e}", self.drop_in.display()),
+        }
+        Ok(())
+    }
+
+    fn status(&self) -> DnsStatus {
+        let configured = fs::read_to_string(&self.drop_in).is_ok_and(|c| c == self.drop_in_conf());
+        DnsStatus {
+            provider: self.kind(),
+            server: Some(self.dnsmasq.server_status()),
+            configured,
+            details: format!("~{} via 127.0.0.1:{} ({})", TLD, self.dnsmasq.port, self.drop_in.display()),
+        }
+    }
+}
+
+/// Lists each recipe's site in a marked block of the hosts file.
+struct Hosts {
+    file: PathBuf,
+}
+
+impl DnsProvider for Hosts {
+    fn kind(&self) -> DnsProviderKind {
+        DnsProviderKind::Hosts
+    }
+
+    fn configure(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn sync_sites(&self, sites: &[String]) -> Result<()> {
+        let current = match fs::read_to_string(&self.file) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let updated = with_hosts_block(&current, sites).ok_or_else(|| {
+            FurnaceError::Config(format!(
+                "{} has an unmatched `{}` or `{}` line; fix or remove it by hand",
+                self.file.display(),
+                HOSTS_BEGIN,
+                HOSTS_END
+            ))
+        })?;
+        if updated != current {
+            replace_file(&self.file, &updated)?;
+            info!("Updated Furnace sites in {}", self.file.display());
+        }
+        Ok(())
+    }
+
+    fn status(&self) -> DnsStatus {
+        let content = fs::read_to_string(&self.file).unwrap_or_default();
+        DnsStatus {
+            provider: self.kind(),
+            server: None,
+            configured: content.lines().any(|l| l.trim() == HOSTS_BEGIN),
+            details: self.file.display().to_string(),
+        }
+    }
+}
+
+fn system_hosts_file() -> PathBuf {
+    if cfg!(windows) {
+        PathBuf::from(r"C:\Windows\System32\drivers\etc\hosts")
+    } else {
+        PathBuf::from("/etc/hosts")
+    }
+}
+
+/// Writes `content` to a temp file beside `path` and renames it over the
+/// original, so a failure never leaves it truncated.
+fn replace_file(path: &Path, content: &str) -> Result<()> {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+    std::io::Write::write_all(&mut tmp, content.as_bytes())?;
+    if let Ok(meta) = fs::metadata(path) {
+        fs::set_permissions(tmp.path(), meta.permissions())?;
+    }
+    tmp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Replaces Furnace's block in a hosts file with one entry per site,
+/// dropping the block when there are none. Other lines are kept as-is.
+/// `None` if only one marker is present, or the end comes first.
+fn with_hosts_block(content: &str, sites: &[String]) -> Option<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let begin = lines.iter().position(|l| l.trim() == HOSTS_BEGIN);
+    let end = lines.iter().position(|l| l.trim() == HOSTS_END);
+    let block = match (begin, end) {
+        (Some(begin), Some(end)) if begin < end => Some(begin..=end),
+        (None, None) => None,
+        _ => return None,
+    };
+    let mut out = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        if !block.as_ref().is_some_and(|b| b.contains(&i)) {
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    if !sites.is_empty() {
+        out.push_str(HOSTS_BEGIN);
+        out.push('\n');
+        for site in sites {
+            out.push_str(&format!("127.0.0.1 {}\n", site));
+        }
+        out.push_str(HOSTS_END);
+        out.push('\n');
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hosts_block_tracks_sites() {
+        let original = "127.0.0.1 localhost\n::1 localhost\n";
+        let sites = vec!["shop.test".to_string(), "blog.test".to_string()];
+        let with = with_hosts_block(original, &sites).unwrap();
+        assert_eq!(
+            with,
+            "127.0.0.1 localhost\n::1 localhost\n# BEGIN furnace\n127.0.0.1 shop.test\n127.0.0.1 blog.test\n# END furnace\n"
+        );
+        assert_eq!(with_hosts_block(&with, &sites).unwrap(), with);
+        assert_eq!(with_hosts_block(&with, &[]).unwrap(), original);
+    }
+
+    #[test]
+    fn unterminated_hosts_block_is_left_alone() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("hosts");
+        let content = "# BEGIN furnace\n127.0.0.1 blog.test\n127.0.0.1 localhost\n";
+        fs::write(&file, content).unwrap();
+        assert_eq!(with_hosts_block(content, &[]), None);
+
+        let hosts = Hosts { file: file.clone() };
+        assert!(matches!(hosts.sync_sites(&["shop.test".to_string()]), Err(FurnaceError::Config(_))));
+        assert_eq!(fs::read_to_string(&file).unwrap(), content);
+    }
+
+    #[test]
+    fn resolved_routes_only_test_lookups() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path().join("home"));
+        let resolved = Resolved {
+            dnsmasq: Dnsmasq { paths, port: 53535 },
+            drop_in: tmp.path().join("resolved.conf.d/furnace.conf"),
+        };
+        resolved.configure().unwrap();
+        let conf = fs::read_to_string(&resolved.drop_in).unwrap();
+        assert_eq!(conf, "[Resolve]\nDNS=127.0.0.1:53535\nDomains=~test\n");
+        assert!(resolved.status().configured);
+
+        resolved.unconfigure().unwrap();
+        assert!(!resolved.drop_in.exists());
+    }
+}
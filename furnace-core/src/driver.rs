@@ -0,0 +1,192 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::{FurnaceError, Result};
+
+/// Knows how to recognise and serve one kind of PHP project, like Valet's drivers.
+pub trait ProjectDriver: Sync {
+    /// Name stored in `Recipe.driver` and accepted by `--driver`
+    fn name(&self) -> &'static str;
+    /// Returns true if the project at `dir` is of this kind
+    fn detect(&self, dir: &Path) -> bool;
+    /// Directory the web server serves
+    fn document_root(&self, dir: &Path) -> PathBuf {
+        dir.join("public")
+    }
+    /// Script every unmatched request is routed to, if any
+    fn front_controller(&self) -> Option<&'static str> {
+        Some("index.php")
+    }
+    /// Extra nginx `location` blocks placed inside the server block
+    fn nginx_locations(&self) -> &'static str {
+        ""
+    }
+}
+
+pub struct Laravel;
+pub struct Symfony;
+pub struct WordPress;
+pub struct Drupal;
+pub struct CraftCms;
+pub struct StaticHtml;
+pub struct PlainPhp;
+
+impl ProjectDriver for Laravel {
+    fn name(&self) -> &'static str {
+        "laravel"
+    }
+    fn detect(&self, dir: &Path) -> bool {
+        crate::recipe::is_laravel_project(dir)
+    }
+}
+
+impl ProjectDriver for Symfony {
+    fn name(&self) -> &'static str {
+        "symfony"
+    }
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("bin/console").exists()
+            && (dir.join("symfony.lock").exists() || dir.join("config/bundles.php").exists())
+    }
+}
+
+impl ProjectDriver for WordPress {
+    fn name(&self) -> &'static str {
+        "wordpress"
+    }
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("wp-config.php").exists() || dir.join("wp-config-sample.php").exists()
+    }
+    fn document_root(&self, dir: &Path) -> PathBuf {
+        dir.to_path_buf()
+    }
+    fn nginx_locations(&self) -> &'static str {
+        r#"    location ~* /(?:uploads|files)/.*\.php$ {
+        deny all;
+    }
+"#
+    }
+}
+
+impl ProjectDriver for Drupal {
+    fn name(&self) -> &'static str {
+        "drupal"
+    }
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("web/core/lib/Drupal.php").exists() || dir.join("core/lib/Drupal.php").exists()
+    }
+    fn document_root(&self, dir: &Path) -> PathBuf {
+        if dir.join("web/core/lib/Drupal.php").exists() {
+            dir.join("web")
+        } else {
+            dir.to_path_buf()
+        }
+    }
+    fn nginx_locations(&self) -> &'static str {
+        r#"    location ~ ^/sites/.*/private/ {
+        return 403;
+    }
+
+    location ~ \..*/.*\.php$ {
+        return 403;
+    }
+"#
+    }
+}
+
+impl ProjectDriver for CraftCms {
+    fn name(&self) -> &'static str {
+        "craftcms"
+    }
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("craft").exists() && dir.join("composer.json").exists()
+    }
+    fn document_root(&self, dir: &Path) -> PathBuf {
+        dir.join("web")
+    }
+}
+
+impl ProjectDriver for StaticHtml {
+    fn name(&self) -> &'static str {
+        "static"
+    }
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("index.html").exists() || dir.join("public/index.html").exists()
+    }
+    fn document_root(&self, dir: &Path) -> PathBuf {
+        if dir.join("public/index.html").exists() {
+            dir.join("public")
+        } else {
+            dir.to_path_buf()
+        }
+    }
+    fn front_controller(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+impl ProjectDriver for PlainPhp {
+    fn name(&self) -> &'static str {
+        "php"
+    }
+    fn detect(&self, dir: &Path) -> bool {
+        dir.join("index.php").exists() || dir.join("public/index.php").exists()
+    }
+    fn document_root(&self, dir: &Path) -> PathBuf {
+        if dir.join("public/index.php").exists() {
+            dir.join("public")
+        } else {
+            dir.to_path_buf()
+        }
+    }
+}
+
+/// Built-in drivers in detection order, most specific first.
+pub fn drivers() -> [&'static dyn ProjectDriver; 7] {
+    [&Laravel, &Symfony, &CraftCms, &Drupal, &WordPress, &PlainPhp, &StaticHtml]
+}
+
+/// Finds the first driver that recognises the project at `dir`.
+pub fn detect(dir: &Path) -> Option<&'static dyn ProjectDriver> {
+    drivers().into_iter().find(|d| d.detect(dir))
+}
+
+/// Looks up a driver by name.
+pub fn by_name(name: &str) -> Result<&'static dyn ProjectDriver> {
+    drivers()
+        .into_iter()
+        .find(|d| d.name() == name)
+        .ok_or_else(|| FurnaceError::UnknownDriver(name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn detects_common_layouts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+
+        fs::write(dir.join("index.html"), "").unwrap();
+        assert_eq!(detect(dir).unwrap().name(), "static");
+
+        fs::write(dir.join("index.php"), "").unwrap();
+        assert_eq!(detect(dir).unwrap().name(), "php");
+
+        fs::write(dir.join("wp-config.php"), "").unwrap();
+        let wp = detect(dir).unwrap();
+        assert_eq!(wp.name(), "wordpress");
+        assert_eq!(wp.document_root(dir), dir);
+
+        fs::write(dir.join("artisan"), "").unwrap();
+        fs::write(dir.join("composer.json"), "{}").unwrap();
+        assert_eq!(detect(dir).unwrap().name(), "laravel");
+    }
+
+    #[test]
+    fn unknown_driver_is_an_error() {
+        assert!(by_name("laravel").is_ok());
+        assert!(matches!(by_name("rails"), Err(FurnaceError::UnknownDriver(_))));
+    }
+}
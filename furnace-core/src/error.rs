@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize, Serializer};
+use thiserror::Error;
+
+/// Errors returned by furnace-core.
+#[derive(Debug, Error)]
+pub enum FurnaceError {
+    #[error("could not determine home directory")]
+    HomeDirNotFound,
+    #[error("recipe '{0}' not found")]
+    RecipeNotFound(String),
+    #[error("{0} is not installed or not in PATH")]
+    BinaryNotFound(String),
+    #[error("{service} config validation failed:\n{stderr}")]
+    ConfigValidation { service: String, stderr: String },
+    #[error("port {0} is already in use; stop the other web server or change the port in your Furnace config")]
+    PortInUse(u16),
+    #[error("port {port} is held by {holders}; stop it or rerun with --force to kill it")]
+    PortHeld { port: u16, holders: String },
+    #[error("furnace daemon: {0}")]
+    Daemon(String),
+    /// An error the daemon hit while handling a request
+    #[error("{message}")]
+    Remote { kind: ErrorKind, message: String },
+    #[error("{0} is already running; stop it with `furnace stop` first")]
+    AlreadyRunning(String),
+    #[error("PHP {version} is used by {recipes}; rerun with --force to move them to another version")]
+    PhpInUse { version: String, recipes: String },
+    #[error("failed to download {url}: {reason}")]
+    Download { url: String, reason: String },
+    #[error("invalid configuration: {0}")]
+    Config(String),
+    #[error("invalid PHP index: {0}")]
+    InvalidIndex(String),
+    #[error("refusing to install {url}: {reason}")]
+    Verification { url: String, reason: String },
+    #[error("failed to extract archive: {0}")]
+    Extraction(String),
+    #[error("{0}")]
+    InvalidProject(String),
+    #[error("unknown web server '{0}' in serve_with (expected nginx or apache)")]
+    UnknownBackend(String),
+    #[error("unknown project driver '{0}' (expected laravel, symfony, wordpress, drupal, craftcms, static or php)")]
+    UnknownDriver(String),
+    #[error("certificate error: {0}")]
+    Certificate(String),
+    #[error("PHP {version} is not available for {platform} in the repository")]
+    PhpVersionUnavailable { version: String, platform: String },
+    #[error("PHP {0} is not installed")]
+    PhpNotInstalled(String),
+    #[error("extension '{extension}' is not available for PHP {version}")]
+    ExtensionNotFound { version: String, extension: String },
+    #[error("`{command}` failed: {reason}")]
+    CommandFailed { command: String, reason: String },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, FurnaceError>;
+
+/// Broad category of a [`FurnaceError`]. The daemon sends it along with the
+/// message so clients can treat remote errors like local ones.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Malformed project, index or data file
+    Invalid,
+    /// A recipe, PHP version or extension that doesn't exist
+    NotFound,
+    /// A required program or download is unavailable
+    Unavailable,
+    /// Failed in a way only a bug or a broken tool explains
+    #[default]
+    Internal,
+    Io,
+    /// Something else holds a port, version or process Furnace needs
+    Conflict,
+    /// Furnace's own configuration is invalid
+    Config,
+}
+
+impl FurnaceError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            FurnaceError::InvalidProject(_)
+            | FurnaceError::UnknownBackend(_)
+            | FurnaceError::UnknownDriver(_)
+            | FurnaceError::Verification { .. }
+            | FurnaceError::InvalidIndex(_)
+            | FurnaceError::Yaml(_)
+            | FurnaceError::Json(_) => ErrorKind::Invalid,
+            FurnaceError::RecipeNotFound(_)
+            | FurnaceError::PhpNotInstalled(_)
+            | FurnaceError::ExtensionNotFound { .. }
+            | FurnaceError::PhpVersionUnavailable { .. } => ErrorKind::NotFound,
+            FurnaceError::BinaryNotFound(_) | FurnaceError::Download { .. } => ErrorKind::Unavailable,
+            FurnaceError::CommandFailed { .. } | FurnaceError::Certificate(_) | FurnaceError::Daemon(_) => {
+                ErrorKind::Internal
+            }
+            FurnaceError::Extraction(_) | FurnaceError::Io(_) => ErrorKind::Io,
+            FurnaceError::PortInUse(_)
+            | FurnaceError::PortHeld { .. }
+            | FurnaceError::PhpInUse { .. }
+            | FurnaceError::AlreadyRunning(_) => ErrorKind::Conflict,
+            FurnaceError::HomeDirNotFound | FurnaceError::ConfigValidation { .. } | FurnaceError::Config(_) => {
+                ErrorKind::Config
+            }
+            FurnaceError::Remote { kind, .. } => *kind,
+        }
+    }
+
+    /// Builds a `CommandFailed` from a finished process, preferring its stderr as the reason.
+    pub fn command_failed(command: &str, output: &std::process::Output) -> Self {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        let reason = if stderr.is_empty() {
+            format!("exited with {}", output.status)
+        } else {
+            stderr
+        };
+        FurnaceError::CommandFailed { command: command.to_string(), reason }
+    }
+}
+
+// Tauri commands serialize errors to send them to the frontend.
+impl Serialize for FurnaceError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
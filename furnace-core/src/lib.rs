@@ -2,12 +2,33 @@ pub fn add(left: u64, right: u64) -> u64 {
     left + right
 }
 
+pub mod assets;
+pub mod config;
+pub mod daemon;
+pub mod dns;
+pub mod driver;
+pub mod error;
+pub mod logs;
 pub mod recipe;
 pub mod services;
+pub mod paths;
 pub mod php;
-pub mod nginx_util;
+pub mod php_build;
+pub mod php_constraint;
+pub mod php_index;
+pub mod php_ini;
+pub mod php_installation;
+pub mod php_pool;
+pub mod process;
+pub mod status;
+pub mod supervisor;
+pub mod template;
+pub mod tls;
 pub mod web_service;
 
+pub use error::{ErrorKind, FurnaceError};
+pub use paths::FurnacePaths;
+
 #[cfg(test)]
 mod tests {
     use super::*;
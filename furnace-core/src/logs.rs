@@ -0,0 +1,43 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+use crate::recipe;
+
+/// Log file for `service`: `nginx`, `apache`, `dnsmasq`, `php-<version>` or
+/// a recipe name for its site's error log.
+pub fn log_file(paths: &FurnacePaths, service: &str) -> Result<PathBuf> {
+    match service {
+        "nginx" => Ok(paths.nginx_logs_dir().join("error.log")),
+        "apache" => Ok(paths.apache_logs_dir().join("error.log")),
+        "dnsmasq" => Ok(paths.dnsmasq_log()),
+        _ => {
+            if let Some(version) = service.strip_prefix("php-") {
+                return Ok(paths.php_version_dir(version).join("php-fpm.log"));
+            }
+            let recipe = recipe::find_recipe(paths, service).map_err(|_| {
+                FurnaceError::Config(format!(
+                    "unknown log '{}'; expected nginx, apache, dnsmasq, php-<version> or a recipe name",
+                    service
+                ))
+            })?;
+            let logs_dir = match recipe.serve_with.as_str() {
+                "apache" => paths.apache_logs_dir(),
+                _ => paths.nginx_logs_dir(),
+            };
+            Ok(logs_dir.join(format!("{}.error.log", recipe.name)))
+        }
+    }
+}
+
+/// The last `lines` lines of a service's log; empty if it has none yet.
+pub fn tail(paths: &FurnacePaths, service: &str, lines: usize) -> Result<Vec<String>> {
+    let content = match fs::read_to_string(log_file(paths, service)?) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let all: Vec<&str> = content.lines().collect();
+    Ok(all[all.len().saturating_sub(lines)..].iter().map(|l| l.to_string()).collect())
+}
@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::{FurnaceError, Result};
+
+/// Environment variable that overrides the Furnace home directory.
+pub const FURNACE_HOME_ENV: &str = "FURNACE_HOME";
+
+/// Locations of everything Furnace manages, rooted at the Furnace home
+/// directory (`~/.furnace` unless overridden).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FurnacePaths {
+    root: PathBuf,
+}
+
+impl FurnacePaths {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        FurnacePaths { root: root.into() }
+    }
+
+    /// Resolve the home directory from an explicit override (e.g. `--home`),
+    /// then `FURNACE_HOME`, then `~/.furnace`. Relative overrides are made
+    /// absolute, since servers and the daemon run from other directories.
+    pub fn resolve(home: Option<PathBuf>) -> Result<Self> {
+        if let Some(home) = home {
+            return Ok(Self::new(std::path::absolute(home)?));
+        }
+        if let Some(home) = std::env::var_os(FURNACE_HOME_ENV).filter(|v| !v.is_empty()) {
+            return Ok(Self::new(std::path::absolute(home)?));
+        }
+        let home = dirs::home_dir().ok_or(FurnaceError::HomeDirNotFound)?;
+        Ok(Self::new(home.join(".furnace")))
+    }
+
+    /// Same as [`FurnacePaths::resolve`] without an explicit override.
+    pub fn from_env() -> Result<Self> {
+        Self::resolve(None)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn config_file(&self) -> PathBuf {
+        self.root.join("config.yml")
+    }
+
+    pub fn recipes_dir(&self) -> PathBuf {
+        self.root.join("recipes")
+    }
+
+    pub fn recipe_file(&self, name: &str) -> PathBuf {
+        self.recipes_dir().join(format!("{}.yml", name))
+    }
+
+    pub fn nginx_dir(&self) -> PathBuf {
+        self.root.join("nginx")
+    }
+
+    pub fn nginx_servers_dir(&self) -> PathBuf {
+        self.nginx_dir().join("servers")
+    }
+
+    pub fn nginx_logs_dir(&self) -> PathBuf {
+        self.nginx_dir().join("logs")
+    }
+
+    pub fn nginx_pid(&self) -> PathBuf {
+        self.nginx_logs_dir().join("nginx.pid")
+    }
+
+    pub fn apache_dir(&self) -> PathBuf {
+        self.root.join("apache")
+    }
+
+    pub fn apache_sites_dir(&self) -> PathBuf {
+        self.apache_dir().join("sites")
+    }
+
+    pub fn apache_logs_dir(&self) -> PathBuf {
+        self.apache_dir().join("logs")
+    }
+
+    pub fn apache_pid(&self) -> PathBuf {
+        self.apache_logs_dir().join("httpd.pid")
+    }
+
+    pub fn php_dir(&self) -> PathBuf {
+        self.root.join("php")
+    }
+
+    pub fn php_version_dir(&self, version: &str) -> PathBuf {
+        self.php_dir().join(version)
+    }
+
+    pub fn php_fpm_socket(&self, version: &str) -> PathBuf {
+        self.php_version_dir(version).join("php-fpm.sock")
+    }
+
+    /// Per-project pool configs included by the version's FPM config.
+    pub fn php_pool_dir(&self, version: &str) -> PathBuf {
+        self.php_version_dir(version).join("pool.d")
+    }
+
+    pub fn php_pool_socket(&self, version: &str, recipe: &str) -> PathBuf {
+        self.php_pool_dir(version).join(format!("{}.sock", recipe))
+    }
+
+    pub fn php_conf_d(&self, version: &str) -> PathBuf {
+        self.php_version_dir(version).join("conf.d")
+    }
+
+    pub fn bin_dir(&self) -> PathBuf {
+        self.root.join("bin")
+    }
+
+    pub fn php_manifest(&self) -> PathBuf {
+        self.php_dir().join("manifest.yml")
+    }
+
+    pub fn php_build_dir(&self, version: &str) -> PathBuf {
+        self.root.join("build").join(format!("php-{}", version))
+    }
+
+    pub fn templates_dir(&self) -> PathBuf {
+        self.root.join("templates")
+    }
+
+    pub fn repository_file(&self) -> PathBuf {
+        self.root.join("repository.yml")
+    }
+
+    /// Copy of the bundled repository to start overrides from; never loaded.
+    pub fn repository_example_file(&self) -> PathBuf {
+        self.root.join("repository.example.yml")
+    }
+
+    pub fn ca_dir(&self) -> PathBuf {
+        self.root.join("ca")
+    }
+
+    pub fn ca_cert(&self) -> PathBuf {
+        self.ca_dir().join("furnace-ca.pem")
+    }
+
+    pub fn ca_key(&self) -> PathBuf {
+        self.ca_dir().join("furnace-ca-key.pem")
+    }
+
+    pub fn certs_dir(&self) -> PathBuf {
+        self.root.join("certs")
+    }
+
+    pub fn site_cert(&self, site: &str) -> PathBuf {
+        self.certs_dir().join(format!("{}.crt", site))
+    }
+
+    pub fn site_key(&self, site: &str) -> PathBuf {
+        self.certs_dir().join(format!("{}.key", site))
+    }
+
+    pub fn cache_dir(&self) -> PathBuf {
+        self.root.join("cache")
+    }
+
+    pub fn php_index_cache(&self) -> PathBuf {
+        self.cache_dir().join("php-index.yml")
+    }
+
+    pub fn php_index_meta(&self) -> PathBuf {
+        self.cache_dir().join("php-index.json")
+    }
+
+    pub fn dnsmasq_dir(&self) -> PathBuf {
+        self.root.join("dnsmasq.d")
+    }
+
+    /// Config for the dnsmasq instance Furnace runs itself.
+    pub fn dnsmasq_conf(&self) -> PathBuf {
+        self.dnsmasq_dir().join("furnace.conf")
+    }
+
+    pub fn dnsmasq_pid(&self) -> PathBuf {
+        self.dnsmasq_dir().join("dnsmasq.pid")
+    }
+
+    pub fn dnsmasq_log(&self) -> PathBuf {
+        self.dnsmasq_dir().join("dnsmasq.log")
+    }
+
+    /// Address and token of the running `furnace daemon`.
+    pub fn daemon_file(&self) -> PathBuf {
+        self.root.join("daemon.json")
+    }
+}
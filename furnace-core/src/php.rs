@@ -1,16 +1,56 @@
 use std::fs;
 use std::path::{Path, PathBuf};
-use serde::Deserialize;
-use dirs;
-use std::io::{self, Cursor, Write, Read};
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Read};
 use indicatif::{ProgressBar, ProgressStyle};
+use sha2::{Digest, Sha256};
+use tracing::{error, info, warn};
+
+use crate::config::FurnaceConfig;
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+use crate::php_build::{self, SourceBuild};
+use crate::php_index;
+use crate::php_ini;
+use crate::php_pool;
+use crate::php_installation::{self, InstallKind, PhpInstallation};
+use crate::process::StopOutcome;
+use crate::template::{self, Template};
+use crate::recipe::{self, load_recipe, Recipe};
+use crate::services;
+use crate::web_service::Backends;
 
 #[derive(Debug, Deserialize)]
 pub struct Repository {
+    /// Index format version; local repository files may omit it
+    #[serde(default = "default_index_version")]
+    pub version: u32,
     pub php: std::collections::HashMap<String, PlatformUrls>,
 }
 
-#[derive(Debug, Deserialize)]
+fn default_index_version() -> u32 {
+    php_index::PHP_INDEX_VERSION
+}
+
+impl Repository {
+    /// Overlays `overrides` onto this repository, per version and platform.
+    pub fn merge(&mut self, overrides: Repository) {
+        for (version, platforms) in overrides.php {
+            let entry = self.php.entry(version).or_default();
+            if platforms.linux.is_some() {
+                entry.linux = platforms.linux;
+            }
+            if platforms.windows.is_some() {
+                entry.windows = platforms.windows;
+            }
+            if platforms.macos.is_some() {
+                entry.macos = platforms.macos;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
 pub struct PlatformUrls {
     pub linux: Option<PhpSource>,
     pub windows: Option<PhpSource>,
@@ -23,12 +63,106 @@ pub struct PhpSource {
     pub command: Option<String>,
     #[serde(rename = "type")]
     pub archive_type: Option<String>,
+    /// Hex SHA-256 of the archive at `url`
+    pub sha256: Option<String>,
+    /// Published checksum list (`<digest>  <file>` lines, as in
+    /// `sha256sum` output) to look the archive's digest up in instead
+    pub sha256_list: Option<String>,
+    pub signature: Option<PhpSignature>,
+    /// Build from a php-src tarball instead
+    pub source: Option<SourceBuild>,
+}
+
+/// Detached signature published alongside a PHP archive.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PhpSignature {
+    Minisign { url: String, public_key: String },
+    /// Checked with `gpg --verify` against the user's keyring
+    Gpg { url: String },
+}
+
+/// Options for [`php_install`].
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Extract downloads without checking their checksum or signature
+    pub skip_verify: bool,
 }
 
-pub fn load_repository() -> Result<Repository, Box<dyn std::error::Error>> {
-    let repo_path = dirs::home_dir().unwrap().join(".furnace/repository.yml");
-    let content = fs::read_to_string(repo_path)?;
-    Ok(serde_yaml::from_str(&content)?)
+/// Repository compiled into Furnace, used until an index is fetched.
+pub const BUNDLED_REPOSITORY: &str = include_str!("../assets/repository.yml");
+
+/// Defaults earlier releases copied to `~/.furnace/repository.yml`, via
+/// `furnace install` and then `furnace assets extract`.
+const PREVIOUS_DEFAULT_REPOSITORIES: [&str; 2] = [
+    r#"php:
+  "8.2":
+    windows:
+      url: "https://windows.php.net/downloads/releases/php-8.2.12-Win32-vs16-x64.zip"
+    macos:
+      command: "brew install php@8.2"
+    linux:
+      command: "sudo apt install php8.2"
+"#,
+    r#"php:
+  "8.2":
+    windows:
+      url: "https://windows.php.net/downloads/releases/php-8.2.12-Win32-vs16-x64.zip"
+      type: "zip"
+    macos:
+      command: "brew install php@8.2"
+    linux:
+      command: "sudo apt install php8.2"
+"#,
+];
+
+/// Loads the bundled repository, then the cached PHP index, then the user's
+/// `repository.yml`, each layered on top of the previous one. A
+/// `repository.yml` that is just a stale copy of the defaults is skipped so
+/// it doesn't shadow the index.
+pub fn load_repository(paths: &FurnacePaths) -> Result<Repository> {
+    let mut repo: Repository = serde_yaml::from_str(BUNDLED_REPOSITORY)?;
+    if let Some(index) = php_index::load_cached(paths)? {
+        repo.merge(index);
+    }
+    let repo_path = paths.repository_file();
+    if repo_path.exists() {
+        let content = fs::read_to_string(&repo_path)?;
+        if is_default_copy(&content) {
+            warn!(
+                "Ignoring {}: it is an unmodified copy of the bundled defaults; delete it to silence this",
+                repo_path.display()
+            );
+        } else {
+            repo.merge(serde_yaml::from_str(&content)?);
+        }
+    }
+    Ok(repo)
+}
+
+fn is_default_copy(content: &str) -> bool {
+    let parse = |s: &str| serde_yaml::from_str::<serde_yaml::Value>(s).ok();
+    let Some(content) = parse(content) else {
+        return false;
+    };
+    std::iter::once(BUNDLED_REPOSITORY)
+        .chain(PREVIOUS_DEFAULT_REPOSITORIES)
+        .any(|default| parse(default).as_ref() == Some(&content))
+}
+
+/// Looks up the install source for a version on the current platform.
+fn find_source<'a>(repo: &'a Repository, version: &str) -> Result<&'a PhpSource> {
+    let platform = detect_platform();
+    repo.php.get(version)
+        .and_then(|p| match platform {
+            "windows" => p.windows.as_ref(),
+            "macos" => p.macos.as_ref(),
+            _ => p.linux.as_ref(),
+        })
+        .ok_or_else(|| FurnaceError::PhpVersionUnavailable {
+            version: version.to_string(),
+            platform: platform.to_string(),
+        })
 }
 
 pub fn detect_platform() -> &'static str {
@@ -41,89 +175,93 @@ pub fn detect_platform() -> &'static str {
     }
 }
 
-pub fn php_install(version: &str) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Preparing to install PHP version {}...", version);
-    let repo = load_repository()?;
+/// Installs `version` with the strategy the repository lists for this
+/// platform and records it in the installation manifest.
+pub fn php_install(paths: &FurnacePaths, version: &str, opts: InstallOptions) -> Result<PhpInstallation> {
+    let kind = install_from_repository(paths, version, &opts)?;
+    let installation = php_installation::detect(paths, version, kind)?;
+    php_installation::record(paths, &installation)?;
+    Ok(installation)
+}
+
+fn install_from_repository(paths: &FurnacePaths, version: &str, opts: &InstallOptions) -> Result<InstallKind> {
+    info!("Preparing to install PHP version {}...", version);
+    let repo = load_repository(paths)?;
     let platform = detect_platform();
-    let source = repo.php.get(version)
-        .and_then(|p| match platform {
-            "windows" => p.windows.as_ref(),
-            "macos" => p.macos.as_ref(),
-            _ => p.linux.as_ref(),
-        })
-        .ok_or("Version/platform not found in repository")?;
-
-    if let Some(url) = &source.url {
-        println!("Downloading PHP from {}", url);
-        let response = reqwest::blocking::get(url)?;
-        let total_size = response.content_length().unwrap_or(0);
-        let pb = ProgressBar::new(total_size);
-        pb.set_style(ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})").unwrap()
-            .progress_chars("#>-")
-        );
-        let mut content = Vec::with_capacity(total_size as usize);
-        let mut source_resp = response;
-        let mut buffer = [0u8; 8192];
-        let mut downloaded = 0u64;
-        loop {
-            let n = source_resp.read(&mut buffer)?;
-            if n == 0 { break; }
-            content.extend_from_slice(&buffer[..n]);
-            downloaded += n as u64;
-            pb.set_position(downloaded);
+    let source = find_source(&repo, version)?;
+
+    if let Some(build) = &source.source {
+        php_build::build(paths, version, build, opts)?;
+        Ok(InstallKind::Source)
+    } else if let Some(url) = &source.url {
+        info!("Downloading PHP from {}", url);
+        let content = download(url)?;
+        if opts.skip_verify {
+            warn!("SKIPPING VERIFICATION of {}: the archive's checksum and signature are NOT checked", url);
+        } else {
+            let sha256 = match (&source.sha256, &source.sha256_list) {
+                (None, Some(list)) => listed_sha256(list, url)?,
+                (sha256, _) => sha256.clone(),
+            };
+            verify_download(sha256.as_deref(), source.signature.as_ref(), url, &content)?;
+            info!("Verified PHP archive.");
         }
-        pb.finish_with_message("Download complete.");
-        let php_dir = dirs::home_dir().unwrap().join(format!(".furnace/php/{}", version));
+        let php_dir = paths.php_version_dir(version);
         fs::create_dir_all(&php_dir)?;
-        println!("Extracting PHP archive...");
+        info!("Extracting PHP archive...");
         match source.archive_type.as_deref() {
             Some("zip") => {
                 let reader = Cursor::new(&content);
-                let mut zip = zip::ZipArchive::new(reader)?;
-                zip.extract(&php_dir)?;
+                zip::ZipArchive::new(reader)
+                    .and_then(|mut zip| zip.extract(&php_dir))
+                    .map_err(|e| FurnaceError::Extraction(e.to_string()))?;
             }
             Some("tar.gz") => {
                 let tar = flate2::read::GzDecoder::new(Cursor::new(&content));
                 let mut archive = tar::Archive::new(tar);
-                archive.unpack(&php_dir)?;
+                archive.unpack(&php_dir)
+                    .map_err(|e| FurnaceError::Extraction(e.to_string()))?;
             }
-            Some(other) => return Err(format!("Unknown archive type: {}", other).into()),
-            None => return Err("Missing archive type for url-based PHP source".into()),
+            Some(other) => return Err(FurnaceError::Extraction(format!("unknown archive type: {}", other))),
+            None => return Err(FurnaceError::Extraction("missing archive type for url-based PHP source".to_string())),
         }
-        println!("Extraction complete.");
-        println!("Verifying PHP binaries...");
+        info!("Extraction complete.");
+        info!("Verifying PHP binaries...");
         let php_bin = if platform == "windows" {
             php_dir.join("php.exe")
         } else {
             php_dir.join("bin/php")
         };
         if !php_bin.exists() {
-            return Err("php binary not found after extraction".into());
+            return Err(FurnaceError::Extraction("php binary not found after extraction".to_string()));
         }
         if platform != "windows" && !php_dir.join("sbin/php-fpm").exists() {
-            return Err("php-fpm binary not found after extraction".into());
+            return Err(FurnaceError::Extraction("php-fpm binary not found after extraction".to_string()));
         }
-        println!("PHP {} installed at {}", version, php_dir.display());
-        Ok(())
+        info!("PHP {} installed at {}", version, php_dir.display());
+        Ok(InstallKind::Archive)
     } else if let Some(cmd) = &source.command {
-        println!("Running install command: {}", cmd);
+        info!("Running install command: {}", cmd);
         let mut parts = cmd.split_whitespace();
-        let program = parts.next().ok_or("Invalid command")?;
+        let program = parts.next().ok_or_else(|| FurnaceError::CommandFailed {
+            command: cmd.clone(),
+            reason: "empty install command".to_string(),
+        })?;
         let args: Vec<&str> = parts.collect();
         let status = std::process::Command::new(program)
             .args(&args)
-            .spawn()?
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => FurnaceError::BinaryNotFound(program.to_string()),
+                _ => FurnaceError::Io(e),
+            })?
             .wait()?;
         if status.success() {
-            println!("PHP {} installed via command.", version);
+            info!("PHP {} installed via command.", version);
             if detect_platform() == "macos" && cmd.contains("brew install") {
-                let home = dirs::home_dir().unwrap();
-                let furnace_php_dir = home.join(format!(".furnace/php/{}", version));
+                let furnace_php_dir = paths.php_version_dir(version);
                 if let Some(parent) = furnace_php_dir.parent() {
-                    if let Err(e) = std::fs::create_dir_all(parent) {
-                        eprintln!("Failed to create parent directory for symlink: {e}");
-                    }
+                    std::fs::create_dir_all(parent)?;
                 }
                 let brew_prefix_output = std::process::Command::new("brew")
                     .arg("--prefix")
@@ -136,49 +274,150 @@ pub fn php_install(version: &str) -> Result<(), Box<dyn std::error::Error>> {
                         if php_path.exists() {
                             let _ = std::fs::remove_file(&furnace_php_dir);
                             let _ = std::fs::remove_dir_all(&furnace_php_dir);
-                            if let Err(e) = std::os::unix::fs::symlink(php_path, &furnace_php_dir) {
-                                eprintln!("Failed to create symlink: {e}");
-                            } else {
-                                println!("Symlinked {} to {}", php_path.display(), furnace_php_dir.display());
-                            }
+                            #[cfg(unix)]
+                            std::os::unix::fs::symlink(php_path, &furnace_php_dir)?;
+                            info!("Symlinked {} to {}", php_path.display(), furnace_php_dir.display());
                         } else {
-                            eprintln!("brew prefix path does not exist: {}", php_path.display());
+                            error!("brew prefix path does not exist: {}", php_path.display());
                         }
                     } else {
-                        eprintln!("brew --prefix failed: {}", String::from_utf8_lossy(&output.stderr));
+                        return Err(FurnaceError::command_failed("brew --prefix", &output));
                     }
                 } else {
-                    eprintln!("Failed to run brew --prefix");
+                    return Err(FurnaceError::BinaryNotFound("brew".to_string()));
                 }
             }
-            Ok(())
+            Ok(InstallKind::Command)
         } else {
-            Err(format!("Command failed with status: {}", status).into())
+            Err(FurnaceError::CommandFailed {
+                command: cmd.clone(),
+                reason: format!("exited with {}", status),
+            })
         }
     } else {
-        Err("No url or command found for this platform/version".into())
+        Err(FurnaceError::PhpVersionUnavailable {
+            version: version.to_string(),
+            platform: platform.to_string(),
+        })
     }
 }
 
-pub fn php_list() {
-    let base = dirs::home_dir().unwrap().join(".furnace/php");
-    if let Ok(entries) = fs::read_dir(base) {
-        for entry in entries.flatten() {
-            if entry.path().is_dir() {
-                println!("{}", entry.file_name().to_string_lossy());
+/// Downloads `url` into memory with a progress bar.
+pub(crate) fn download(url: &str) -> Result<Vec<u8>> {
+    let download_err = |reason: String| FurnaceError::Download { url: url.to_string(), reason };
+    let response = reqwest::blocking::get(url)
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| download_err(e.to_string()))?;
+    let total_size = response.content_length().unwrap_or(0);
+    let pb = ProgressBar::new(total_size);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})").unwrap()
+        .progress_chars("#>-")
+    );
+    let mut content = Vec::with_capacity(total_size as usize);
+    let mut source_resp = response;
+    let mut buffer = [0u8; 8192];
+    let mut downloaded = 0u64;
+    loop {
+        let n = source_resp.read(&mut buffer).map_err(|e| download_err(e.to_string()))?;
+        if n == 0 { break; }
+        content.extend_from_slice(&buffer[..n]);
+        downloaded += n as u64;
+        pb.set_position(downloaded);
+    }
+    pb.finish_with_message("Download complete.");
+    Ok(content)
+}
+
+/// Checks a downloaded archive against its published sha256 and signature.
+pub(crate) fn verify_download(
+    sha256: Option<&str>,
+    signature: Option<&PhpSignature>,
+    url: &str,
+    content: &[u8],
+) -> Result<()> {
+    let refuse = |reason: String| FurnaceError::Verification { url: url.to_string(), reason };
+    let expected = sha256.ok_or_else(|| {
+        refuse("the repository has no sha256 for it (use --skip-verify to install anyway)".to_string())
+    })?;
+    let actual = hex::encode(Sha256::digest(content));
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(refuse(format!("sha256 mismatch: expected {}, got {}", expected.trim(), actual)));
+    }
+    match signature {
+        None => Ok(()),
+        Some(PhpSignature::Minisign { url: sig_url, public_key }) => {
+            let sig = String::from_utf8(fetch(sig_url)?).map_err(|e| refuse(e.to_string()))?;
+            let public_key = minisign_verify::PublicKey::from_base64(public_key)
+                .map_err(|e| refuse(format!("invalid minisign public key: {e}")))?;
+            let signature = minisign_verify::Signature::decode(&sig)
+                .map_err(|e| refuse(format!("invalid minisign signature: {e}")))?;
+            public_key
+                .verify(content, &signature, false)
+                .map_err(|e| refuse(format!("minisign signature check failed: {e}")))
+        }
+        Some(PhpSignature::Gpg { url: sig_url }) => {
+            let sig = fetch(sig_url)?;
+            let tmp = tempfile::tempdir()?;
+            let (archive_path, sig_path) = (tmp.path().join("archive"), tmp.path().join("archive.sig"));
+            fs::write(&archive_path, content)?;
+            fs::write(&sig_path, sig)?;
+            let output = std::process::Command::new("gpg")
+                .arg("--batch")
+                .arg("--verify")
+                .arg(&sig_path)
+                .arg(&archive_path)
+                .output();
+            let output = output.map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => FurnaceError::BinaryNotFound("gpg".to_string()),
+                _ => FurnaceError::Io(e),
+            })?;
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(refuse(format!(
+                    "gpg signature check failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )))
             }
         }
     }
 }
 
-pub fn php_use(version: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let home = dirs::home_dir().unwrap();
-    let php_dir = home.join(format!(".furnace/php/{}", version));
-    if !php_dir.exists() {
-        return Err(format!("PHP version {} is not installed (expected at {})", version, php_dir.display()).into());
-    }
-    let cwd = std::env::current_dir()?;
-    let config_path = cwd.join(".furnace.yml");
+/// Looks up the digest published for `url`'s file name in a checksum list.
+fn listed_sha256(list_url: &str, url: &str) -> Result<Option<String>> {
+    let list = String::from_utf8_lossy(&fetch(list_url)?).to_string();
+    let file = url.rsplit('/').next().unwrap_or(url);
+    Ok(sha256_from_list(&list, file))
+}
+
+fn sha256_from_list(list: &str, file: &str) -> Option<String> {
+    list.lines().find_map(|line| {
+        let mut cols = line.split_whitespace();
+        let digest = cols.next()?;
+        // sha256sum marks binary-mode entries with a leading '*'
+        (cols.next()?.trim_start_matches('*') == file).then(|| digest.to_string())
+    })
+}
+
+fn fetch(url: &str) -> Result<Vec<u8>> {
+    let download_err = |reason: String| FurnaceError::Download { url: url.to_string(), reason };
+    reqwest::blocking::get(url)
+        .and_then(|r| r.error_for_status())
+        .and_then(|r| r.bytes())
+        .map(|b| b.to_vec())
+        .map_err(|e| download_err(e.to_string()))
+}
+
+/// Installed PHP versions, sorted by version.
+pub fn php_list(paths: &FurnacePaths) -> Result<Vec<PhpInstallation>> {
+    Ok(php_installation::load_manifest(paths)?.into_values().collect())
+}
+
+/// Pins `version` for the project at `project_dir` and regenerates its vhosts.
+pub fn php_use(paths: &FurnacePaths, project_dir: &Path, version: &str) -> Result<()> {
+    php_installation::installation(paths, version)?;
+    let config_path = project_dir.join(".furnace.yml");
     let mut config: serde_yaml::Value = if config_path.exists() {
         serde_yaml::from_str(&fs::read_to_string(&config_path)?)?
     } else {
@@ -186,141 +425,267 @@ pub fn php_use(version: &str) -> Result<(), Box<dyn std::error::Error>> {
     };
     config["php_version"] = serde_yaml::Value::String(version.to_string());
     fs::write(config_path, serde_yaml::to_string(&config)?)?;
-    println!("Set PHP version {} for project", version);
-    let recipe_path = cwd.join(".furnace.recipe.yml");
+    let recipe_path = project_dir.join(".furnace.recipe.yml");
     if recipe_path.exists() {
-        let recipe: Result<serde_yaml::Value, _> = serde_yaml::from_str(&fs::read_to_string(&recipe_path)?);
-        if let Ok(mut recipe_yaml) = recipe {
-            recipe_yaml["php_version"] = serde_yaml::Value::String(version.to_string());
-            fs::write(&recipe_path, serde_yaml::to_string(&recipe_yaml)?)?;
-            let home = dirs::home_dir().unwrap();
-            let project_name = recipe_yaml["name"].as_str().unwrap_or("project");
-            let cwd_str = recipe_yaml["path"].as_str().unwrap_or("");
-            let php_version_raw = recipe_yaml["php_version"].as_str().unwrap_or(version);
-            let php_version = sanitize_php_version(php_version_raw);
-            let site = recipe_yaml["site"].as_str().unwrap_or("localhost");
-            let apache_dir = home.join(".furnace/apache");
-            fs::create_dir_all(&apache_dir).ok();
-            let apache_conf_path = apache_dir.join(format!("{}.conf", project_name));
-            let logs_dir = apache_dir.join("logs");
-            fs::create_dir_all(&logs_dir).ok();
-            let php_fpm_socket = php_dir.join("php-fpm.sock").to_string_lossy().to_string();
-            let apache_conf = format!(r#"
-<VirtualHost *:80>
-    ServerName {site}
-    DocumentRoot "{project_path}/public"
-
-    <Directory "{project_path}/public">
-        AllowOverride All
-        Require all granted
-    </Directory>
-
-    <FilesMatch \.php$>
-        SetHandler "proxy:unix:{php_fpm_socket}|fcgi://localhost/"
-    </FilesMatch>
-
-    ErrorLog "{logs_dir}/{project}.error.log"
-    CustomLog "{logs_dir}/{project}.access.log" combined
-</VirtualHost>
-"#,
-                site=site,
-                project_path=cwd_str,
-                php_fpm_socket=php_fpm_socket,
-                logs_dir=logs_dir.to_string_lossy(),
-                project=project_name
-            );
-            fs::write(&apache_conf_path, apache_conf).ok();
-            let nginx_dir = home.join(".furnace/nginx");
-            fs::create_dir_all(&nginx_dir).ok();
-            let nginx_conf_path = nginx_dir.join(format!("{}.conf", project_name));
-            let nginx_logs_dir = nginx_dir.join("logs");
-            fs::create_dir_all(&nginx_logs_dir).ok();
-            let nginx_conf = format!(r#"
-server {{
-    listen 80;
-    server_name {site};
-    root {project_path}/public;
-
-    index index.php index.html;
-
-    access_log {logs_dir}/{project}.access.log;
-    error_log {logs_dir}/{project}.error.log;
-
-    location / {{
-        try_files $uri $uri/ /index.php?$query_string;
-    }}
-
-    location ~ \.php$ {{
-        include /opt/homebrew/etc/nginx/fastcgi_params;
-        fastcgi_pass unix:{php_fpm_socket};
-        fastcgi_param SCRIPT_FILENAME $document_root$fastcgi_script_name;
-        fastcgi_index index.php;
-    }}
-}}
-"#,
-                site=site,
-                project_path=cwd_str,
-                logs_dir=nginx_logs_dir.to_string_lossy(),
-                php_fpm_socket=php_fpm_socket,
-                project=project_name
-            );
-            fs::write(&nginx_conf_path, &nginx_conf).ok();
-            let servers_dir = nginx_dir.join("servers");
-            if let Err(e) = fs::create_dir_all(&servers_dir) {
-                eprintln!("Failed to create nginx servers dir: {e}");
-            }
-            let server_conf_path = servers_dir.join(format!("{}.conf", project_name));
-            if let Err(e) = fs::write(&server_conf_path, &nginx_conf) {
-                eprintln!("Failed to write nginx conf to servers dir: {e}");
+        let mut recipe = load_recipe(paths, &recipe_path)?;
+        recipe.php_version = version.to_string();
+        fs::write(&recipe_path, serde_yaml::to_string(&recipe)?)?;
+        php_pool::sync(paths, &recipe)?;
+        let backends = Backends::new(paths);
+        let backend = backends.for_recipe(&recipe)?;
+        backend.write_conf(&recipe, &FurnaceConfig::load(paths)?)?;
+        info!("Updated {} config for project {}", backend.name(), recipe.name);
+        if backend.is_running() {
+            backend.test_config()?;
+            if let Err(e) = backend.reload() {
+                error!("Failed to reload {}: {e}", backend.name());
             }
-            println!("Updated Apache and Nginx config for project {}", project_name);
-            if let Some(project_name) = recipe_yaml["name"].as_str() {
-                if let Err(e) = crate::nginx_util::register_nginx_conf(project_name) {
-                    eprintln!("Failed to register nginx config: {e}");
-                }
+        }
+    }
+    php_fpm_conf(paths, version)?;
+    Ok(())
+}
+
+/// What [`php_fpm_conf`] and the running FPM master put in a version's
+/// directory.
+const FURNACE_VERSION_FILES: &[&str] =
+    &["furnace-php-fpm.conf", "conf.d", "pool.d", "php-fpm.pid", "php-fpm.sock", "php-fpm.log"];
+
+/// Result of [`php_uninstall`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct UninstallReport {
+    pub version: String,
+    pub removed: Vec<PathBuf>,
+    /// Recipes moved to another version by `--force`
+    pub reassigned: Vec<Recipe>,
+}
+
+/// Stops and removes an installed PHP version. Recipes still using it block
+/// the removal unless `force` is set, which moves them to the best remaining
+/// version. Only Furnace's own files go: a brew symlink is unlinked without
+/// touching the keg, and package-manager or linked binaries stay in place.
+pub fn php_uninstall(paths: &FurnacePaths, version: &str, force: bool) -> Result<UninstallReport> {
+    php_installation::installation(paths, version)?;
+    let users: Vec<Recipe> = recipe::get_recipes(paths)?
+        .into_iter()
+        .filter(|r| r.php_version == version)
+        .collect();
+    let others: Vec<String> = php_installation::load_manifest(paths)?
+        .into_keys()
+        .filter(|v| v != version)
+        .collect();
+    if !users.is_empty() && (!force || others.is_empty()) {
+        let recipes = users.iter().map(|r| r.name.as_str()).collect::<Vec<_>>().join(", ");
+        if force {
+            return Err(FurnaceError::Config(format!(
+                "no other PHP version is installed to move {} to",
+                recipes
+            )));
+        }
+        return Err(FurnaceError::PhpInUse { version: version.to_string(), recipes });
+    }
+    // A pin would put the recipe straight back on this version next refresh
+    for user in &users {
+        if let Some((file, _)) = recipe::php_version_pin(Path::new(&user.path)).filter(|(_, v)| v == version) {
+            return Err(FurnaceError::Config(format!(
+                "{} pins {} to PHP {}; change its php_version before uninstalling",
+                file.display(),
+                user.name,
+                version
+            )));
+        }
+    }
+
+    let stopped = services::stop_php_fpm(paths, version);
+    if stopped.outcome == StopOutcome::Failed {
+        return Err(FurnaceError::AlreadyRunning(stopped.name));
+    }
+
+    let mut reassigned = Vec::new();
+    for mut user in users {
+        let resolved = recipe::project_php_version(paths, Path::new(&user.path))?;
+        user.php_version = if others.contains(&resolved) {
+            resolved
+        } else {
+            others.iter().max_by_key(|v| version_key(v)).cloned().unwrap_or(resolved)
+        };
+        recipe::apply(paths, &user)?;
+        php_pool::sync(paths, &user)?;
+        reassigned.push(user);
+    }
+
+    let mut removed = Vec::new();
+    let dir = paths.php_version_dir(version);
+    if dir.is_symlink() {
+        // The link points into the keg, so take Furnace's own files out of
+        // it before dropping the link
+        for name in FURNACE_VERSION_FILES {
+            let path = dir.join(name);
+            match fs::symlink_metadata(&path) {
+                Ok(meta) if meta.is_dir() => fs::remove_dir_all(&path)?,
+                Ok(_) => fs::remove_file(&path)?,
+                Err(_) => continue,
             }
-            let _ = std::process::Command::new("nginx")
-                .arg("-p").arg(&nginx_dir)
-                .arg("-c").arg("nginx.conf")
-                .arg("-s").arg("reload")
-                .status();
+            removed.push(path);
         }
+        fs::remove_file(&dir)?;
+        removed.push(dir);
+    } else if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+        removed.push(dir);
     }
-    if let Err(e) = php_fpm_conf(version) {
-        eprintln!("Failed to start PHP-FPM: {e}");
+    let shim = paths.bin_dir().join(format!("php{}", version));
+    if shim.exists() {
+        fs::remove_file(&shim)?;
+        removed.push(shim);
     }
-    Ok(())
+    // Last, so a failure above leaves the version recorded for a retry
+    php_installation::unregister(paths, version)?;
+    Ok(UninstallReport { version: version.to_string(), removed, reassigned })
 }
 
-fn sanitize_php_version(version: &str) -> String {
-    version.chars()
-        .skip_while(|c| !c.is_ascii_digit())
-        .take_while(|c| c.is_ascii_digit() || *c == '.')
+/// Uninstalls every PHP version no recipe uses.
+pub fn php_prune(paths: &FurnacePaths) -> Result<Vec<UninstallReport>> {
+    unused_versions(paths)?
+        .iter()
+        .map(|version| php_uninstall(paths, version, false))
         .collect()
 }
 
-pub fn php_fpm_conf(version: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let repo = load_repository()?;
-    let platform = detect_platform();
-    let source = repo.php.get(version)
-        .and_then(|p| match platform {
-            "windows" => p.windows.as_ref(),
-            "macos" => p.macos.as_ref(),
-            _ => p.linux.as_ref(),
-        })
-        .ok_or("Version/platform not found in repository")?;
-    let php_dir = dirs::home_dir().unwrap().join(format!(".furnace/php/{}", version));
+/// Installed versions no recipe uses, which [`php_prune`] removes.
+pub fn unused_versions(paths: &FurnacePaths) -> Result<Vec<String>> {
+    let recipes = recipe::get_recipes(paths)?;
+    Ok(php_installation::load_manifest(paths)?
+        .into_keys()
+        .filter(|v| !recipes.iter().any(|r| &r.php_version == v))
+        .collect())
+}
+
+/// Sort key for `major.minor` strings, so 8.10 comes after 8.9.
+fn version_key(version: &str) -> Vec<u64> {
+    version.split('.').map(|p| p.parse().unwrap_or(0)).collect()
+}
+
+/// User and group FPM workers and their sockets run as.
+pub(crate) fn fpm_owner() -> (String, String) {
     let user = whoami::username();
     let group = if cfg!(target_os = "macos") { "staff".to_string() } else { user.clone() };
+    (user, group)
+}
+
+pub fn php_fpm_conf(paths: &FurnacePaths, version: &str) -> Result<()> {
+    let installation = php_installation::installation(paths, version)?;
+    let php_dir = paths.php_version_dir(version);
+    fs::create_dir_all(paths.php_conf_d(version))?;
+    fs::create_dir_all(paths.php_pool_dir(version))?;
+    php_ini::write_cli_shim(paths, &installation)?;
+    let (user, group) = fpm_owner();
     let php_fpm_conf_path = php_dir.join("furnace-php-fpm.conf");
     let sock_path = php_dir.join("php-fpm.sock");
-    let tpl_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/php-fpm-template/php-fpm.conf.tpl");
-    let tpl = fs::read_to_string(&tpl_path).expect("php-fpm.conf.tpl missing");
-    let conf = tpl.replace("{php_dir}", &php_dir.to_string_lossy())
-        .replace("{user}", &user)
-        .replace("{group}", &group)
-        .replace("{sock_path}", &sock_path.to_string_lossy());
+    let tpl = template::source(paths, None, Template::PhpFpmConf)?;
+    let conf = template::render_str(&tpl, &[
+        ("php_dir", php_dir.to_string_lossy().to_string()),
+        ("user", user),
+        ("group", group),
+        ("sock_path", sock_path.to_string_lossy().to_string()),
+        ("ini_scan_dir", php_ini::scan_dir_env(paths, version)),
+        ("pool_dir", paths.php_pool_dir(version).to_string_lossy().to_string()),
+    ]);
     fs::write(&php_fpm_conf_path, conf)?;
-    println!("Generated custom furnace-php-fpm.conf at {}", php_fpm_conf_path.display());
+    info!("Generated custom furnace-php-fpm.conf at {}", php_fpm_conf_path.display());
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verify_download_checks_sha256() {
+        let url = "https://example.test/php.zip";
+        let digest = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert!(verify_download(Some(digest), None, url, b"hello").is_ok());
+        assert!(verify_download(Some(&digest.to_uppercase()), None, url, b"hello").is_ok());
+        assert!(matches!(
+            verify_download(Some(digest), None, url, b"tampered"),
+            Err(FurnaceError::Verification { .. })
+        ));
+        assert!(matches!(
+            verify_download(None, None, url, b"hello"),
+            Err(FurnaceError::Verification { .. })
+        ));
+    }
+
+    #[test]
+    fn stale_default_repository_copies_are_ignored() {
+        assert!(PREVIOUS_DEFAULT_REPOSITORIES.iter().all(|copy| is_default_copy(copy)));
+        assert!(is_default_copy(BUNDLED_REPOSITORY));
+        assert!(!is_default_copy("php:\n  \"8.3\":\n    linux:\n      command: \"my-php-installer\"\n"));
+    }
+
+    #[test]
+    fn sha256_is_found_in_published_list() {
+        let list = "aaaa  php-8.2.12-nts-Win32-vs16-x64.zip\nbbbb *php-8.2.12-Win32-vs16-x64.zip\n";
+        assert_eq!(sha256_from_list(list, "php-8.2.12-Win32-vs16-x64.zip").as_deref(), Some("bbbb"));
+        assert_eq!(sha256_from_list(list, "php-8.3.0-Win32-vs16-x64.zip"), None);
+    }
+
+    fn record_version(paths: &FurnacePaths, version: &str, prefix: &Path) {
+        php_installation::record(paths, &php_installation::PhpInstallation {
+            version: version.into(),
+            kind: php_installation::InstallKind::Command,
+            php: prefix.join("bin/php"),
+            fpm: prefix.join("sbin/php-fpm"),
+            php_ini: None,
+            extension_dir: None,
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn forced_uninstall_refuses_pinned_projects() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path().join("home"));
+        record_version(&paths, "8.1", &paths.php_version_dir("8.1"));
+        record_version(&paths, "8.2", &paths.php_version_dir("8.2"));
+        let project = tmp.path().join("blog");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join(".furnace.yml"), "php_version: \"8.1\"\n").unwrap();
+        let recipe = Recipe {
+            name: "blog".into(),
+            path: project.to_string_lossy().to_string(),
+            php_version: "8.1".into(),
+            serve_with: "nginx".into(),
+            site: "blog.test".into(),
+            driver: "laravel".into(),
+            secure: false,
+            fpm_pool: None,
+            orphaned: false,
+        };
+        fs::create_dir_all(paths.recipes_dir()).unwrap();
+        fs::write(paths.recipe_file("blog"), serde_yaml::to_string(&recipe).unwrap()).unwrap();
+
+        assert!(matches!(php_uninstall(&paths, "8.1", true), Err(FurnaceError::Config(_))));
+        assert!(php_installation::load_manifest(&paths).unwrap().contains_key("8.1"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn uninstalling_a_linked_keg_leaves_only_the_keg() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path().join("home"));
+        let keg = tmp.path().join("keg");
+        fs::create_dir_all(keg.join("bin")).unwrap();
+        fs::write(keg.join("bin/php"), "").unwrap();
+        fs::create_dir_all(paths.php_dir()).unwrap();
+        std::os::unix::fs::symlink(&keg, paths.php_version_dir("8.1")).unwrap();
+        fs::create_dir_all(paths.php_pool_dir("8.1")).unwrap();
+        fs::write(paths.php_version_dir("8.1").join("furnace-php-fpm.conf"), "").unwrap();
+        record_version(&paths, "8.1", &keg);
+
+        php_uninstall(&paths, "8.1", false).unwrap();
+        assert!(!paths.php_version_dir("8.1").is_symlink());
+        let left: Vec<_> = fs::read_dir(&keg).unwrap().flatten().map(|e| e.file_name()).collect();
+        assert_eq!(left, ["bin"]);
+    }
+}
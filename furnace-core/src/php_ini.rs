@@ -0,0 +1,221 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+use crate::php_installation::{self, PhpInstallation};
+use crate::services;
+
+/// ini file in the managed conf.d that holds `furnace php ini set` values.
+const SETTINGS_FILE: &str = "99-furnace.ini";
+
+/// Extensions loaded with `zend_extension` instead of `extension`.
+const ZEND_EXTENSIONS: &[&str] = &["opcache", "xdebug"];
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ExtensionStatus {
+    pub name: String,
+    /// Enabled through Furnace's conf.d
+    pub enabled: bool,
+    /// A shared object for it exists in the extension dir
+    pub available: bool,
+}
+
+/// Sets an ini directive for every FPM worker and CLI run of `version`, then
+/// restarts that version's pool if it is running.
+pub fn ini_set(paths: &FurnacePaths, version: &str, key: &str, value: &str) -> Result<()> {
+    let key = key.trim();
+    if key.is_empty() || key.contains(['=', '[', ']']) || key.contains(char::is_control) {
+        return Err(FurnaceError::Config(format!("invalid ini key '{}'", key.escape_debug())));
+    }
+    // A line break would let the value add directives of its own
+    if value.contains(char::is_control) {
+        return Err(FurnaceError::Config(format!(
+            "invalid value for {}: '{}' contains control characters",
+            key,
+            value.escape_debug()
+        )));
+    }
+    let installation = php_installation::installation(paths, version)?;
+    let mut settings = ini_settings(paths, version)?;
+    match settings.iter_mut().find(|(k, _)| k == key) {
+        Some(setting) => setting.1 = value.trim().to_string(),
+        None => settings.push((key.to_string(), value.trim().to_string())),
+    }
+    let content: String = settings.iter().map(|(k, v)| format!("{} = {}\n", k, v)).collect();
+    fs::create_dir_all(paths.php_conf_d(version))?;
+    fs::write(paths.php_conf_d(version).join(SETTINGS_FILE), content)?;
+    apply(paths, &installation)
+}
+
+/// Directives set with [`ini_set`], in file order.
+pub fn ini_settings(paths: &FurnacePaths, version: &str) -> Result<Vec<(String, String)>> {
+    let path = paths.php_conf_d(version).join(SETTINGS_FILE);
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(content
+        .lines()
+        .filter(|l| !l.trim_start().starts_with(';'))
+        .filter_map(|l| l.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect())
+}
+
+/// Loads `ext` for `version` through the managed conf.d.
+pub fn ext_enable(paths: &FurnacePaths, version: &str, ext: &str) -> Result<()> {
+    let installation = php_installation::installation(paths, version)?;
+    if installation.extension_dir.is_some() && !extension_file(&installation, ext).is_some_and(|f| f.exists()) {
+        return Err(FurnaceError::ExtensionNotFound { version: version.to_string(), extension: ext.to_string() });
+    }
+    let directive = if ZEND_EXTENSIONS.contains(&ext) { "zend_extension" } else { "extension" };
+    fs::create_dir_all(paths.php_conf_d(version))?;
+    fs::write(ext_ini(paths, version, ext), format!("{}={}\n", directive, ext))?;
+    apply(paths, &installation)
+}
+
+/// Stops loading `ext` through the managed conf.d. Extensions loaded by the
+/// installation's own php.ini are not affected.
+pub fn ext_disable(paths: &FurnacePaths, version: &str, ext: &str) -> Result<()> {
+    let installation = php_installation::installation(paths, version)?;
+    let ini = ext_ini(paths, version, ext);
+    if ini.exists() {
+        fs::remove_file(ini)?;
+    }
+    apply(paths, &installation)
+}
+
+/// Shared extensions in the extension dir plus any enabled by Furnace.
+pub fn ext_list(paths: &FurnacePaths, version: &str) -> Result<Vec<ExtensionStatus>> {
+    let installation = php_installation::installation(paths, version)?;
+    let mut names: Vec<String> = Vec::new();
+    if let Some(dir) = &installation.extension_dir
+        && let Ok(entries) = fs::read_dir(dir)
+    {
+        for entry in entries.flatten() {
+            let file = entry.file_name().to_string_lossy().to_string();
+            let name = file
+                .strip_suffix(".so")
+                .or_else(|| file.strip_prefix("php_").and_then(|f| f.strip_suffix(".dll")));
+            if let Some(name) = name {
+                names.push(name.to_string());
+            }
+        }
+    }
+    if let Ok(entries) = fs::read_dir(paths.php_conf_d(version)) {
+        for entry in entries.flatten() {
+            let file = entry.file_name().to_string_lossy().to_string();
+            if let Some(name) = file.strip_prefix("20-").and_then(|f| f.strip_suffix(".ini")) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    Ok(names
+        .into_iter()
+        .map(|name| ExtensionStatus {
+            enabled: ext_ini(paths, version, &name).exists(),
+            available: extension_file(&installation, &name).is_some_and(|f| f.exists()),
+            name,
+        })
+        .collect())
+}
+
+fn ext_ini(paths: &FurnacePaths, version: &str, ext: &str) -> PathBuf {
+    paths.php_conf_d(version).join(format!("20-{}.ini", ext))
+}
+
+fn extension_file(installation: &PhpInstallation, ext: &str) -> Option<PathBuf> {
+    let dir = installation.extension_dir.as_ref()?;
+    if cfg!(target_os = "windows") {
+        Some(dir.join(format!("php_{}.dll", ext)))
+    } else {
+        Some(dir.join(format!("{}.so", ext)))
+    }
+}
+
+/// Value for `PHP_INI_SCAN_DIR`: the installation's own scan dir (the empty
+/// leading entry) followed by Furnace's conf.d.
+pub fn scan_dir_env(paths: &FurnacePaths, version: &str) -> String {
+    let separator = if cfg!(target_os = "windows") { ";" } else { ":" };
+    format!("{}{}", separator, paths.php_conf_d(version).display())
+}
+
+/// Writes `~/.furnace/bin/php<version>`, which runs the CLI with the managed
+/// conf.d loaded.
+pub fn write_cli_shim(paths: &FurnacePaths, installation: &PhpInstallation) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::create_dir_all(paths.bin_dir())?;
+        let shim = paths.bin_dir().join(format!("php{}", installation.version));
+        let script = format!(
+            "#!/bin/sh\nPHP_INI_SCAN_DIR=\"{}\" exec \"{}\" \"$@\"\n",
+            scan_dir_env(paths, &installation.version),
+            installation.php.display()
+        );
+        fs::write(&shim, script)?;
+        fs::set_permissions(&shim, fs::Permissions::from_mode(0o755))?;
+    }
+    #[cfg(not(unix))]
+    let _ = (paths, installation);
+    Ok(())
+}
+
+fn apply(paths: &FurnacePaths, installation: &PhpInstallation) -> Result<()> {
+    write_cli_shim(paths, installation)?;
+    services::restart_php_fpm(paths, installation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::php_installation::InstallKind;
+
+    fn install(paths: &FurnacePaths) -> PhpInstallation {
+        let installation = PhpInstallation {
+            version: "8.3".into(),
+            kind: InstallKind::Linked,
+            php: "/usr/bin/php8.3".into(),
+            fpm: "/usr/sbin/php-fpm8.3".into(),
+            php_ini: None,
+            extension_dir: None,
+        };
+        php_installation::record(paths, &installation).unwrap();
+        installation
+    }
+
+    #[test]
+    fn ini_set_replaces_existing_values() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path());
+        install(&paths);
+        ini_set(&paths, "8.3", "memory_limit", "512M").unwrap();
+        ini_set(&paths, "8.3", "display_errors", "On").unwrap();
+        ini_set(&paths, "8.3", "memory_limit", "1G").unwrap();
+        assert_eq!(
+            ini_settings(&paths, "8.3").unwrap(),
+            vec![("memory_limit".to_string(), "1G".to_string()), ("display_errors".to_string(), "On".to_string())]
+        );
+        assert!(ini_set(&paths, "8.3", "bad=key", "1").is_err());
+        assert!(ini_set(&paths, "8.3", "memory_limit", "1G\nauto_prepend_file=/tmp/x.php").is_err());
+    }
+
+    #[test]
+    fn ext_enable_and_disable() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path());
+        install(&paths);
+        ext_enable(&paths, "8.3", "xdebug").unwrap();
+        let ini = fs::read_to_string(paths.php_conf_d("8.3").join("20-xdebug.ini")).unwrap();
+        assert_eq!(ini, "zend_extension=xdebug\n");
+        assert!(ext_list(&paths, "8.3").unwrap().iter().any(|e| e.name == "xdebug" && e.enabled));
+        ext_disable(&paths, "8.3", "xdebug").unwrap();
+        assert!(ext_list(&paths, "8.3").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+
+/// How an installation got onto the machine.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallKind {
+    /// Prebuilt archive extracted into `~/.furnace/php/<version>`
+    Archive,
+    /// Package manager command such as brew or apt
+    Command,
+    /// Built from php-src into `~/.furnace/php/<version>`
+    Source,
+    /// Found on the system by `furnace php scan`; Furnace never removes it
+    Linked,
+}
+
+impl std::fmt::Display for InstallKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            InstallKind::Archive => "archive",
+            InstallKind::Command => "command",
+            InstallKind::Source => "source",
+            InstallKind::Linked => "linked",
+        };
+        f.write_str(s)
+    }
+}
+
+/// A PHP version Furnace can serve, as recorded in `~/.furnace/php/manifest.yml`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhpInstallation {
+    pub version: String,
+    pub kind: InstallKind,
+    pub php: PathBuf,
+    /// php-fpm, or php-cgi on Windows
+    pub fpm: PathBuf,
+    pub php_ini: Option<PathBuf>,
+    pub extension_dir: Option<PathBuf>,
+}
+
+/// Finds the binaries for a freshly installed `version` and asks PHP where
+/// its php.ini and extensions live.
+pub fn detect(paths: &FurnacePaths, version: &str, kind: InstallKind) -> Result<PhpInstallation> {
+    let prefix = paths.php_version_dir(version);
+    let php = php_candidates(&prefix, version)
+        .into_iter()
+        .find(|p| p.exists())
+        .ok_or_else(|| FurnaceError::BinaryNotFound(format!("php {}", version)))?;
+    let fpm = fpm_candidates(&prefix, version)
+        .into_iter()
+        .find(|p| p.exists())
+        .ok_or_else(|| FurnaceError::BinaryNotFound(format!("php-fpm {}", version)))?;
+    // Package-manager installs live outside ~/.furnace; the version dir still
+    // holds Furnace's fpm config and socket
+    fs::create_dir_all(&prefix)?;
+    let (php_ini, extension_dir) = query_php(&php);
+    Ok(PhpInstallation { version: version.to_string(), kind, php, fpm, php_ini, extension_dir })
+}
+
+fn php_candidates(prefix: &Path, version: &str) -> Vec<PathBuf> {
+    vec![
+        prefix.join("bin/php"),
+        prefix.join("php.exe"),
+        PathBuf::from(format!("/usr/bin/php{}", version)),
+        PathBuf::from(format!("/opt/homebrew/opt/php@{}/bin/php", version)),
+        PathBuf::from(format!("/usr/local/opt/php@{}/bin/php", version)),
+    ]
+}
+
+fn fpm_candidates(prefix: &Path, version: &str) -> Vec<PathBuf> {
+    vec![
+        prefix.join("sbin/php-fpm"),
+        prefix.join("php-cgi.exe"),
+        PathBuf::from(format!("/usr/sbin/php-fpm{}", version)),
+        PathBuf::from(format!("/usr/sbin/php{}-fpm", version)),
+        PathBuf::from(format!("/opt/homebrew/opt/php@{}/sbin/php-fpm", version)),
+        PathBuf::from(format!("/usr/local/opt/php@{}/sbin/php-fpm", version)),
+    ]
+}
+
+/// Loaded php.ini and extension_dir as reported by the binary itself.
+pub fn query_php(php: &Path) -> (Option<PathBuf>, Option<PathBuf>) {
+    let output = Command::new(php)
+        .arg("-r")
+        .arg(r#"echo php_ini_loaded_file() ?: "", "\n", ini_get("extension_dir");"#)
+        .output();
+    let Ok(output) = output else {
+        return (None, None);
+    };
+    if !output.status.success() {
+        return (None, None);
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines().map(str::trim);
+    let non_empty = |s: Option<&str>| s.filter(|s| !s.is_empty()).map(PathBuf::from);
+    (non_empty(lines.next()), non_empty(lines.next()))
+}
+
+/// Result of [`scan`].
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct ScanReport {
+    pub registered: Vec<PhpInstallation>,
+    pub skipped: Vec<ScanSkip>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ScanSkip {
+    pub php: PathBuf,
+    pub reason: String,
+}
+
+/// Finds PHP binaries on PATH and in well-known prefixes (Homebrew, asdf,
+/// phpenv, Remi) and registers those with an FPM binary as linked
+/// installations. Versions Furnace installed itself are left alone.
+pub fn scan(paths: &FurnacePaths) -> Result<ScanReport> {
+    let mut manifest = load_manifest(paths)?;
+    let mut report = ScanReport::default();
+    let mut seen = Vec::new();
+    for candidate in scan_candidates() {
+        let Ok(php) = candidate.canonicalize() else { continue };
+        if seen.contains(&php) {
+            continue;
+        }
+        seen.push(php.clone());
+        let Some(version) = php_version(&php) else {
+            report.skipped.push(ScanSkip { php, reason: "`php -v` did not report a version".to_string() });
+            continue;
+        };
+        if let Some(existing) = manifest.get(&version)
+            && (existing.kind != InstallKind::Linked || report.registered.iter().any(|r| r.version == version))
+        {
+            report.skipped.push(ScanSkip { php, reason: format!("PHP {} is already registered", version) });
+            continue;
+        }
+        let config_prefix = php_config(&php, "--prefix").map(PathBuf::from);
+        let fpm = config_prefix
+            .iter()
+            .map(|prefix| prefix.join("sbin/php-fpm"))
+            .chain(fpm_candidates(&paths.php_version_dir(&version), &version))
+            .find(|p| p.exists());
+        let Some(fpm) = fpm else {
+            report.skipped.push(ScanSkip { php, reason: format!("no php-fpm found for PHP {}", version) });
+            continue;
+        };
+        let (php_ini, extension_dir) = query_php(&php);
+        let extension_dir = extension_dir.or_else(|| php_config(&php, "--extension-dir").map(PathBuf::from));
+        fs::create_dir_all(paths.php_version_dir(&version))?;
+        let installation = PhpInstallation { version: version.clone(), kind: InstallKind::Linked, php, fpm, php_ini, extension_dir };
+        manifest.insert(version, installation.clone());
+        report.registered.push(installation);
+    }
+    save_manifest(paths, &manifest)?;
+    Ok(report)
+}
+
+fn scan_candidates() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::env::var_os("PATH")
+        .map(|path| std::env::split_paths(&path).collect())
+        .unwrap_or_default();
+    dirs.extend(["/usr/bin", "/usr/local/bin", "/opt/homebrew/bin"].map(PathBuf::from));
+    let mut parents = vec![PathBuf::from("/opt/homebrew/opt"), PathBuf::from("/usr/local/opt")];
+    // Without a home directory these would resolve against the working directory
+    if let Some(home) = dirs::home_dir() {
+        parents.extend([home.join(".asdf/installs/php"), home.join(".phpenv/versions")]);
+    }
+    parents.push(PathBuf::from("/opt/remi"));
+    for parent in parents {
+        if let Ok(entries) = fs::read_dir(parent) {
+            for entry in entries.flatten() {
+                dirs.push(entry.path().join("bin"));
+                dirs.push(entry.path().join("root/usr/bin"));
+            }
+        }
+    }
+    let mut candidates = Vec::new();
+    for dir in dirs {
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if is_php_binary_name(&name) {
+                    candidates.push(entry.path());
+                }
+            }
+        }
+    }
+    candidates
+}
+
+/// `php`, `php.exe` or a versioned name such as Debian's `php8.2`.
+fn is_php_binary_name(name: &str) -> bool {
+    let rest = name.strip_suffix(".exe").unwrap_or(name);
+    match rest.strip_prefix("php") {
+        Some("") => true,
+        Some(version) => version.chars().all(|c| c.is_ascii_digit() || c == '.')
+            && version.starts_with(|c: char| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+/// Major.minor version from `php -v`.
+fn php_version(php: &Path) -> Option<String> {
+    let output = Command::new(php).arg("-v").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_php_v(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_php_v(output: &str) -> Option<String> {
+    let full = output.lines().next()?.strip_prefix("PHP ")?.split_whitespace().next()?;
+    let mut parts = full.split('.');
+    Some(format!("{}.{}", parts.next()?, parts.next()?))
+}
+
+/// Asks the `php-config` next to `php` (e.g. `php-config8.2` for `php8.2`).
+fn php_config(php: &Path, option: &str) -> Option<String> {
+    let name = php.file_name()?.to_string_lossy().replacen("php", "php-config", 1);
+    let output = Command::new(php.with_file_name(name)).arg(option).output().ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty()).then_some(value)
+}
+
+/// All recorded installations keyed by version. Without a manifest, versions
+/// earlier releases unpacked or linked under `~/.furnace/php` are detected
+/// and recorded first.
+pub fn load_manifest(paths: &FurnacePaths) -> Result<BTreeMap<String, PhpInstallation>> {
+    match fs::read_to_string(paths.php_manifest()) {
+        Ok(content) => Ok(serde_yaml::from_str(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => migrate_version_dirs(paths),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn migrate_version_dirs(paths: &FurnacePaths) -> Result<BTreeMap<String, PhpInstallation>> {
+    let mut manifest = BTreeMap::new();
+    let Ok(entries) = fs::read_dir(paths.php_dir()) else {
+        return Ok(manifest);
+    };
+    for entry in entries.flatten() {
+        let version = entry.file_name().to_string_lossy().to_string();
+        if !is_version_dir_name(&version) {
+            continue;
+        }
+        // Brew installs were symlinked into place; archives were unpacked
+        let kind = if entry.path().is_symlink() { InstallKind::Command } else { InstallKind::Archive };
+        match detect(paths, &version, kind) {
+            Ok(installation) => {
+                info!("Recorded PHP {} found in {}", version, entry.path().display());
+                manifest.insert(version, installation);
+            }
+            Err(e) => warn!("Not recording {}: {e}", entry.path().display()),
+        }
+    }
+    if !manifest.is_empty() {
+        save_manifest(paths, &manifest)?;
+    }
+    Ok(manifest)
+}
+
+/// `8.2`-style major.minor directory names.
+fn is_version_dir_name(name: &str) -> bool {
+    let mut parts = name.split('.');
+    let numeric = |p: Option<&str>| p.is_some_and(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()));
+    numeric(parts.next()) && numeric(parts.next()) && parts.next().is_none()
+}
+
+fn save_manifest(paths: &FurnacePaths, manifest: &BTreeMap<String, PhpInstallation>) -> Result<()> {
+    fs::create_dir_all(paths.php_dir())?;
+    fs::write(paths.php_manifest(), serde_yaml::to_string(manifest)?)?;
+    Ok(())
+}
+
+/// Adds or replaces an installation in the manifest.
+pub fn record(paths: &FurnacePaths, installation: &PhpInstallation) -> Result<()> {
+    let mut manifest = load_manifest(paths)?;
+    manifest.insert(installation.version.clone(), installation.clone());
+    save_manifest(paths, &manifest)
+}
+
+/// Drops `version` from the manifest, returning what was recorded.
+pub fn unregister(paths: &FurnacePaths, version: &str) -> Result<Option<PhpInstallation>> {
+    let mut manifest = load_manifest(paths)?;
+    let removed = manifest.remove(version);
+    if removed.is_some() {
+        save_manifest(paths, &manifest)?;
+    }
+    Ok(removed)
+}
+
+/// The recorded installation for `version`.
+pub fn installation(paths: &FurnacePaths, version: &str) -> Result<PhpInstallation> {
+    load_manifest(paths)?
+        .remove(version)
+        .ok_or_else(|| FurnaceError::PhpNotInstalled(version.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_and_record_extracted_install() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path());
+        let prefix = paths.php_version_dir("8.3");
+        fs::create_dir_all(prefix.join("bin")).unwrap();
+        fs::create_dir_all(prefix.join("sbin")).unwrap();
+        fs::write(prefix.join("bin/php"), "").unwrap();
+        fs::write(prefix.join("sbin/php-fpm"), "").unwrap();
+
+        let found = detect(&paths, "8.3", InstallKind::Archive).unwrap();
+        assert_eq!(found.fpm, prefix.join("sbin/php-fpm"));
+        record(&paths, &found).unwrap();
+        assert_eq!(installation(&paths, "8.3").unwrap().php, prefix.join("bin/php"));
+        assert!(matches!(installation(&paths, "7.4"), Err(FurnaceError::PhpNotInstalled(_))));
+    }
+
+    #[test]
+    fn version_dirs_without_a_manifest_are_recorded() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path());
+        let prefix = paths.php_version_dir("8.1");
+        fs::create_dir_all(prefix.join("bin")).unwrap();
+        fs::create_dir_all(prefix.join("sbin")).unwrap();
+        fs::write(prefix.join("bin/php"), "").unwrap();
+        fs::write(prefix.join("sbin/php-fpm"), "").unwrap();
+        fs::create_dir_all(paths.php_dir().join("cache")).unwrap();
+
+        let manifest = load_manifest(&paths).unwrap();
+        assert_eq!(manifest.keys().collect::<Vec<_>>(), ["8.1"]);
+        assert_eq!(manifest["8.1"].kind, InstallKind::Archive);
+        assert!(paths.php_manifest().exists());
+    }
+
+    #[test]
+    fn recognizes_php_binaries_and_versions() {
+        assert!(is_php_binary_name("php"));
+        assert!(is_php_binary_name("php8.2"));
+        assert!(is_php_binary_name("php.exe"));
+        assert!(!is_php_binary_name("php-fpm"));
+        assert!(!is_php_binary_name("phpize"));
+        assert_eq!(
+            parse_php_v("PHP 8.2.12 (cli) (built: Oct 24 2023 21:15:35) (NTS)\nCopyright (c) The PHP Group"),
+            Some("8.2".to_string())
+        );
+        assert_eq!(parse_php_v("Usage: php [options]"), None);
+    }
+}
@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+use crate::php::{self, fpm_owner};
+use crate::php_ini;
+use crate::php_installation;
+use crate::recipe::Recipe;
+use crate::services;
+use crate::template::{self, Template};
+
+/// A dedicated FPM pool for one project, declared under `fpm_pool` in its
+/// `.furnace.yml`. Unset fields match the shared pool.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct FpmPool {
+    /// `dynamic`, `static` or `ondemand`
+    pub pm: String,
+    pub max_children: u32,
+    pub start_servers: u32,
+    pub min_spare_servers: u32,
+    pub max_spare_servers: u32,
+    /// Environment variables passed to workers
+    pub env: BTreeMap<String, String>,
+    /// ini directives the project's scripts can't override
+    pub php_admin_value: BTreeMap<String, String>,
+}
+
+impl Default for FpmPool {
+    fn default() -> Self {
+        Self {
+            pm: "dynamic".to_string(),
+            max_children: 5,
+            start_servers: 2,
+            min_spare_servers: 1,
+            max_spare_servers: 3,
+            env: BTreeMap::new(),
+            php_admin_value: BTreeMap::new(),
+        }
+    }
+}
+
+/// The `fpm_pool` section of the project's `.furnace.yml`, if any.
+pub fn from_project(dir: &Path) -> Result<Option<FpmPool>> {
+    let project_yml = dir.join(".furnace.yml");
+    let content = match fs::read_to_string(&project_yml) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let yaml: serde_yaml::Value = serde_yaml::from_str(&content)?;
+    let Some(section) = yaml.get("fpm_pool").filter(|v| !v.is_null()) else {
+        return Ok(None);
+    };
+    let pool: FpmPool = serde_yaml::from_value(section.clone())?;
+    if !["dynamic", "static", "ondemand"].contains(&pool.pm.as_str()) {
+        return Err(FurnaceError::InvalidProject(format!(
+            "fpm_pool.pm in {} must be dynamic, static or ondemand, not '{}'",
+            project_yml.display(),
+            pool.pm
+        )));
+    }
+    // Each entry becomes one line of the pool config
+    for (section, settings) in [("env", &pool.env), ("php_admin_value", &pool.php_admin_value)] {
+        if let Some((key, _)) = settings.iter().find(|(k, v)| k.contains(['\n', '\r']) || v.contains(['\n', '\r'])) {
+            return Err(FurnaceError::InvalidProject(format!(
+                "fpm_pool.{}.{} in {} must fit on one line",
+                section,
+                key.trim(),
+                project_yml.display()
+            )));
+        }
+    }
+    Ok(Some(pool))
+}
+
+/// Socket the recipe's vhost passes PHP requests to.
+pub fn socket(paths: &FurnacePaths, recipe: &Recipe) -> PathBuf {
+    match recipe.fpm_pool {
+        Some(_) => paths.php_pool_socket(&recipe.php_version, &recipe.name),
+        None => paths.php_fpm_socket(&recipe.php_version),
+    }
+}
+
+/// Writes the recipe's pool config, dropping copies left under other PHP
+/// versions, and restarts every FPM master whose pools changed.
+pub fn sync(paths: &FurnacePaths, recipe: &Recipe) -> Result<()> {
+    let mut changed = remove_stale(paths, &recipe.name, recipe.fpm_pool.as_ref().map(|_| recipe.php_version.as_str()))?;
+    if let Some(pool) = &recipe.fpm_pool {
+        let conf = render(paths, recipe, pool)?;
+        let conf_path = pool_conf(paths, &recipe.php_version, &recipe.name);
+        if fs::read_to_string(&conf_path).ok().as_deref() != Some(conf.as_str()) {
+            fs::create_dir_all(paths.php_pool_dir(&recipe.php_version))?;
+            fs::write(&conf_path, conf)?;
+            info!("FPM pool for {} written to {}", recipe.name, conf_path.display());
+            changed.push(recipe.php_version.clone());
+        }
+        if include_pools(paths, &recipe.php_version)? {
+            changed.push(recipe.php_version.clone());
+        }
+    }
+    restart(paths, changed);
+    Ok(())
+}
+
+/// Removes the recipe's pool under every PHP version.
+pub fn remove(paths: &FurnacePaths, recipe: &str) -> Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    let versions = remove_stale(paths, recipe, None)?;
+    for version in &versions {
+        removed.push(pool_conf(paths, version, recipe));
+    }
+    restart(paths, versions);
+    Ok(removed)
+}
+
+/// Regenerates the version's FPM config if it predates per-project pools
+/// and so never loads `pool.d`. Returns whether it was rewritten.
+fn include_pools(paths: &FurnacePaths, version: &str) -> Result<bool> {
+    if !php_installation::load_manifest(paths)?.contains_key(version) {
+        return Ok(false);
+    }
+    let fpm_conf = paths.php_version_dir(version).join("furnace-php-fpm.conf");
+    let include = format!("include={}/*.conf", paths.php_pool_dir(version).display());
+    if fs::read_to_string(&fpm_conf).is_ok_and(|c| c.contains(&include)) {
+        return Ok(false);
+    }
+    php::php_fpm_conf(paths, version)?;
+    Ok(true)
+}
+
+fn pool_conf(paths: &FurnacePaths, version: &str, recipe: &str) -> PathBuf {
+    paths.php_pool_dir(version).join(format!("{}.conf", recipe))
+}
+
+/// Deletes the recipe's pool config under every version except `keep` and
+/// returns the versions it was removed from.
+fn remove_stale(paths: &FurnacePaths, recipe: &str, keep: Option<&str>) -> Result<Vec<String>> {
+    let mut versions = Vec::new();
+    let Ok(entries) = fs::read_dir(paths.php_dir()) else {
+        return Ok(versions);
+    };
+    for entry in entries.flatten() {
+        let version = entry.file_name().to_string_lossy().to_string();
+        let conf = pool_conf(paths, &version, recipe);
+        if Some(version.as_str()) != keep && conf.exists() {
+            fs::remove_file(&conf)?;
+            info!("Deleted {}", conf.display());
+            versions.push(version);
+        }
+    }
+    Ok(versions)
+}
+
+fn render(paths: &FurnacePaths, recipe: &Recipe, pool: &FpmPool) -> Result<String> {
+    let (user, group) = fpm_owner();
+    let mut settings = String::new();
+    for (key, value) in &pool.env {
+        settings.push_str(&format!("env[{}] = {}\n", key, value));
+    }
+    for (key, value) in &pool.php_admin_value {
+        settings.push_str(&format!("php_admin_value[{}] = {}\n", key, value));
+    }
+    let tpl = template::source(paths, Some(Path::new(&recipe.path)), Template::PhpFpmPool)?;
+    Ok(template::render_str(&tpl, &[
+        ("name", recipe.name.clone()),
+        ("user", user),
+        ("group", group),
+        ("sock_path", paths.php_pool_socket(&recipe.php_version, &recipe.name).to_string_lossy().to_string()),
+        ("pm", pool.pm.clone()),
+        ("max_children", pool.max_children.to_string()),
+        ("start_servers", pool.start_servers.to_string()),
+        ("min_spare_servers", pool.min_spare_servers.to_string()),
+        ("max_spare_servers", pool.max_spare_servers.to_string()),
+        ("ini_scan_dir", php_ini::scan_dir_env(paths, &recipe.php_version)),
+        ("settings", settings),
+    ]))
+}
+
+fn restart(paths: &FurnacePaths, mut versions: Vec<String>) {
+    versions.sort();
+    versions.dedup();
+    for version in versions {
+        let Ok(installation) = php_installation::installation(paths, &version) else {
+            continue;
+        };
+        if let Err(e) = services::restart_php_fpm(paths, &installation) {
+            warn!("Failed to restart PHP-FPM {}: {e}", version);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(path: &Path, fpm_pool: Option<FpmPool>) -> Recipe {
+        Recipe {
+            name: "blog".into(),
+            path: path.to_string_lossy().to_string(),
+            php_version: "8.3".into(),
+            serve_with: "nginx".into(),
+            site: "blog.test".into(),
+            driver: "laravel".into(),
+            secure: false,
+            fpm_pool,
+            orphaned: false,
+        }
+    }
+
+    #[test]
+    fn project_pool_is_written_and_moved_between_versions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path().join("home"));
+        let project = tmp.path().join("blog");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            project.join(".furnace.yml"),
+            "fpm_pool:\n  max_children: 12\n  env:\n    APP_ENV: local\n  php_admin_value:\n    memory_limit: 1G\n",
+        )
+        .unwrap();
+        let pool = from_project(&project).unwrap().unwrap();
+        assert_eq!(pool.pm, "dynamic");
+
+        let mut recipe = recipe(&project, Some(pool));
+        sync(&paths, &recipe).unwrap();
+        let conf = fs::read_to_string(pool_conf(&paths, "8.3", "blog")).unwrap();
+        assert!(conf.contains("[furnace-blog]"));
+        assert!(conf.contains("pm.max_children = 12"));
+        assert!(conf.contains("env[APP_ENV] = local"));
+        assert!(conf.contains("php_admin_value[memory_limit] = 1G"));
+        assert_eq!(socket(&paths, &recipe), paths.php_pool_socket("8.3", "blog"));
+
+        recipe.php_version = "8.2".into();
+        sync(&paths, &recipe).unwrap();
+        assert!(!pool_conf(&paths, "8.3", "blog").exists());
+        assert!(pool_conf(&paths, "8.2", "blog").exists());
+
+        assert_eq!(remove(&paths, "blog").unwrap(), vec![pool_conf(&paths, "8.2", "blog")]);
+    }
+
+    #[test]
+    fn fpm_conf_without_pool_include_is_regenerated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path().join("home"));
+        let project = tmp.path().join("blog");
+        fs::create_dir_all(&project).unwrap();
+        php_installation::record(&paths, &php_installation::PhpInstallation {
+            version: "8.3".into(),
+            kind: php_installation::InstallKind::Archive,
+            php: paths.php_version_dir("8.3").join("bin/php"),
+            fpm: paths.php_version_dir("8.3").join("sbin/php-fpm"),
+            php_ini: None,
+            extension_dir: None,
+        })
+        .unwrap();
+        let fpm_conf = paths.php_version_dir("8.3").join("furnace-php-fpm.conf");
+        fs::create_dir_all(paths.php_version_dir("8.3")).unwrap();
+        fs::write(&fpm_conf, "[global]\n\n[www]\nlisten = php-fpm.sock\n").unwrap();
+
+        sync(&paths, &recipe(&project, Some(FpmPool::default()))).unwrap();
+        let conf = fs::read_to_string(&fpm_conf).unwrap();
+        assert!(conf.contains(&format!("include={}/*.conf", paths.php_pool_dir("8.3").display())));
+    }
+
+    #[test]
+    fn unknown_pm_is_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(tmp.path().join(".furnace.yml"), "fpm_pool:\n  pm: lazy\n").unwrap();
+        assert!(matches!(from_project(tmp.path()), Err(FurnaceError::InvalidProject(_))));
+    }
+
+    #[test]
+    fn multiline_values_are_rejected() {
+        let tmp = tempfile::tempdir().unwrap();
+        fs::write(
+            tmp.path().join(".furnace.yml"),
+            "fpm_pool:\n  php_admin_value:\n    memory_limit: \"1G\\nuser = root\"\n",
+        )
+        .unwrap();
+        assert!(matches!(from_project(tmp.path()), Err(FurnaceError::InvalidProject(_))));
+    }
+}
@@ -0,0 +1,240 @@
+//! Signalling and stopping processes through the OS instead of `kill`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{FurnaceError, Result};
+use crate::status::{self, ProcessState};
+
+/// How long a process gets to exit after each signal before escalating.
+const GRACEFUL_TIMEOUT: Duration = Duration::from_secs(10);
+const TERM_TIMEOUT: Duration = Duration::from_secs(5);
+const KILL_TIMEOUT: Duration = Duration::from_secs(2);
+/// Longest [`escalate`] can take for one process.
+pub const MAX_STOP_TIME: Duration = GRACEFUL_TIMEOUT.saturating_add(TERM_TIMEOUT).saturating_add(KILL_TIMEOUT);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Graceful shutdown for nginx and PHP-FPM
+    Quit,
+    Term,
+    Kill,
+    /// PHP-FPM: reload config in place
+    Usr2,
+}
+
+impl std::fmt::Display for Signal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Signal::Quit => "QUIT",
+            Signal::Term => "TERM",
+            Signal::Kill => "KILL",
+            Signal::Usr2 => "USR2",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Sends `signal` to `pid`.
+#[cfg(unix)]
+pub fn signal(pid: u32, signal: Signal) -> Result<()> {
+    let signo = match signal {
+        Signal::Quit => libc::SIGQUIT,
+        Signal::Term => libc::SIGTERM,
+        Signal::Kill => libc::SIGKILL,
+        Signal::Usr2 => libc::SIGUSR2,
+    };
+    let pid = libc::pid_t::try_from(pid).map_err(|_| signal_failed(pid, signal, "PID out of range".to_string()))?;
+    // SAFETY: kill has no memory-safety preconditions
+    if unsafe { libc::kill(pid, signo) } == 0 {
+        Ok(())
+    } else {
+        Err(signal_failed(pid as u32, signal, std::io::Error::last_os_error().to_string()))
+    }
+}
+
+/// Windows has no signals; anything but KILL asks the process to close.
+#[cfg(windows)]
+pub fn signal(pid: u32, signal: Signal) -> Result<()> {
+    let mut command = std::process::Command::new("taskkill");
+    command.arg("/PID").arg(pid.to_string());
+    match signal {
+        Signal::Kill => {
+            command.arg("/F");
+        }
+        Signal::Usr2 => return Err(signal_failed(pid, signal, "not supported on Windows".to_string())),
+        Signal::Quit | Signal::Term => {}
+    }
+    let output = command.output()?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(FurnaceError::command_failed("taskkill", &output))
+    }
+}
+
+fn signal_failed(pid: u32, signal: Signal, reason: String) -> FurnaceError {
+    FurnaceError::CommandFailed { command: format!("kill -{} {}", signal, pid), reason }
+}
+
+/// Returns true if a process with the given PID exists.
+#[cfg(unix)]
+pub fn alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: signal 0 only checks that the process exists
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    // Exists, but belongs to another user
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+pub fn alive(pid: u32) -> bool {
+    // tasklist exits 0 even when no process matches the filter
+    let output = std::process::Command::new("tasklist")
+        .arg("/FI")
+        .arg(format!("PID eq {}", pid))
+        .arg("/NH")
+        .output();
+    matches!(output, Ok(ref o) if String::from_utf8_lossy(&o.stdout).contains(&pid.to_string()))
+}
+
+/// Polls `exited` until it returns true or `timeout` passes.
+pub fn wait_until(timeout: Duration, mut exited: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if exited() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        sleep(POLL_INTERVAL);
+    }
+}
+
+/// How a process ended up after [`stop`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StopOutcome {
+    /// Nothing was running
+    NotRunning,
+    /// Exited after the graceful signal
+    Stopped,
+    /// Needed TERM
+    Terminated,
+    /// Needed KILL
+    Killed,
+    /// Still alive after KILL, or could not be signalled
+    Failed,
+}
+
+impl std::fmt::Display for StopOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            StopOutcome::NotRunning => "not running",
+            StopOutcome::Stopped => "stopped",
+            StopOutcome::Terminated => "terminated",
+            StopOutcome::Killed => "killed",
+            StopOutcome::Failed => "failed to stop",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Result of stopping one process.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StopResult {
+    pub name: String,
+    pub pid: Option<u32>,
+    pub outcome: StopOutcome,
+    /// Stale PID files and sockets removed afterwards
+    pub removed: Vec<PathBuf>,
+}
+
+impl std::fmt::Display for StopResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.pid {
+            Some(pid) => write!(f, "{} (PID {}): {}", self.name, pid, self.outcome),
+            None => write!(f, "{}: {}", self.name, self.outcome),
+        }
+    }
+}
+
+/// Sends `graceful`, then TERM, then KILL, waiting for the process to exit
+/// after each. `exited` reports whether it is gone; callers that own the
+/// process pass a check that also reaps it.
+pub fn escalate(pid: u32, graceful: Signal, mut exited: impl FnMut() -> bool) -> StopOutcome {
+    let steps = [
+        (graceful, GRACEFUL_TIMEOUT, StopOutcome::Stopped),
+        (Signal::Term, TERM_TIMEOUT, StopOutcome::Terminated),
+        (Signal::Kill, KILL_TIMEOUT, StopOutcome::Killed),
+    ];
+    for (i, (sig, timeout, outcome)) in steps.into_iter().enumerate() {
+        if i > 0 && sig == graceful {
+            continue;
+        }
+        if signal(pid, sig).is_err() && !exited() {
+            continue;
+        }
+        if wait_until(timeout, &mut exited) {
+            return outcome;
+        }
+    }
+    StopOutcome::Failed
+}
+
+/// Stops the process named in `pid_file` and removes the PID file and
+/// `sockets` once it is gone.
+pub fn stop(name: &str, pid_file: &Path, graceful: Signal, sockets: &[PathBuf]) -> StopResult {
+    let process = status::process_status(pid_file);
+    let outcome = match process.pid {
+        Some(pid) if process.state == ProcessState::Running => escalate(pid, graceful, || !alive(pid)),
+        _ => StopOutcome::NotRunning,
+    };
+    let mut removed = Vec::new();
+    if outcome != StopOutcome::Failed {
+        for path in std::iter::once(pid_file).chain(sockets.iter().map(PathBuf::as_path)) {
+            if path.exists() && fs::remove_file(path).is_ok() {
+                removed.push(path.to_path_buf());
+            }
+        }
+    }
+    StopResult { name: name.to_string(), pid: process.pid, outcome, removed }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stop_escalates_and_cleans_up() {
+        let tmp = tempfile::tempdir().unwrap();
+        // Ignores QUIT, so stop has to fall back to TERM
+        let mut child = std::process::Command::new("sh")
+            .arg("-c")
+            .arg("trap '' QUIT; exec sleep 30")
+            .spawn()
+            .unwrap();
+        let pid_file = tmp.path().join("test.pid");
+        let socket = tmp.path().join("test.sock");
+        fs::write(&pid_file, child.id().to_string()).unwrap();
+        fs::write(&socket, "").unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+
+        let pid = child.id();
+        let outcome = escalate(pid, Signal::Quit, || matches!(child.try_wait(), Ok(Some(_))));
+        assert_eq!(outcome, StopOutcome::Terminated);
+        let result = stop("test", &pid_file, Signal::Quit, std::slice::from_ref(&socket));
+        assert_eq!(result.outcome, StopOutcome::NotRunning);
+        assert_eq!(result.removed, vec![pid_file, socket]);
+    }
+}
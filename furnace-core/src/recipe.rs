@@ -1,16 +1,86 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use serde::{Serialize, Deserialize};
-use chrono::Local;
-use tracing::{info, error};
+use tracing::{info, error, warn};
 
-#[derive(Serialize, Deserialize, Debug)]
+use crate::config::FurnaceConfig;
+use crate::dns;
+use crate::driver::{self, ProjectDriver};
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+use crate::php;
+use crate::php_constraint::Constraint;
+use crate::php_installation;
+use crate::php_pool::{self, FpmPool};
+use crate::tls;
+use crate::web_service::Backends;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Recipe {
     pub name: String,
     pub path: String,
     pub php_version: String,
     pub serve_with: String,
     pub site: String,
+    /// Project driver; recipes cooked before drivers existed are Laravel
+    #[serde(default = "default_driver")]
+    pub driver: String,
+    /// Served over HTTPS with a certificate from the Furnace CA
+    #[serde(default)]
+    pub secure: bool,
+    /// Dedicated FPM pool from the project's `.furnace.yml`; the version's
+    /// shared pool is used when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fpm_pool: Option<FpmPool>,
+    /// The project directory was moved or deleted after cooking
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub orphaned: bool,
+}
+
+impl Recipe {
+    /// Address the site is reachable at.
+    pub fn url(&self, config: &FurnaceConfig) -> String {
+        config.site_url(&self.site, self.secure)
+    }
+}
+
+fn default_driver() -> String {
+    driver::Laravel.name().to_string()
+}
+
+/// Options for [`cook`].
+#[derive(Debug, Clone, Default)]
+pub struct CookOptions {
+    /// Project name; defaults to the directory name
+    pub name: Option<String>,
+    /// Web server backend; defaults to nginx
+    pub serve_with: Option<String>,
+    /// Force a project driver instead of detecting one
+    pub driver: Option<String>,
+}
+
+/// Which recipe [`dispose`] should remove.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RecipeDisposalTarget {
+    ByName(String),
+    /// The recipe symlinked from `.furnace.recipe.yml` in this project directory
+    ByPath(PathBuf),
+}
+
+/// Result of [`secure`].
+#[derive(Serialize, Debug, Clone)]
+pub struct SecureReport {
+    pub recipe: Recipe,
+    pub cert: PathBuf,
+    /// A new root CA was generated and still has to be trusted
+    pub ca_created: bool,
+}
+
+/// Files removed by [`dispose`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DisposeReport {
+    pub name: String,
+    pub removed: Vec<PathBuf>,
 }
 
 pub fn is_laravel_project<P: AsRef<Path>>(dir: P) -> bool {
@@ -40,148 +110,161 @@ fn extract_major_minor(version: &str) -> String {
     }
 }
 
-pub fn cook_here(name: Option<String>) {
-    let cwd = std::env::current_dir().expect("Failed to get current dir");
-    if !is_laravel_project(&cwd) {
-        error!("Not a Laravel project");
-        return;
+/// Picks the newest installed PHP line satisfying `require.php` in the
+/// project's composer.json. When none is installed, warns and returns the
+/// line to install instead.
+pub fn resolve_php_version(paths: &FurnacePaths, project_dir: &Path) -> Result<String> {
+    let Some(requirement) = parse_php_version(project_dir.join("composer.json")) else {
+        return Ok("unknown".to_string());
+    };
+    let constraint = match Constraint::parse(&requirement) {
+        Ok(constraint) => constraint,
+        Err(e) => {
+            warn!("{e}; falling back to {}", extract_major_minor(&requirement));
+            return Ok(extract_major_minor(&requirement));
+        }
+    };
+    let installed = php_installation::load_manifest(paths)?;
+    if let Some(line) = constraint.best_line(installed.keys().map(String::as_str)) {
+        return Ok(line.to_string());
     }
-    let project_name = name.unwrap_or_else(|| cwd.file_name().unwrap().to_string_lossy().to_string());
-    println!("cooking {}", project_name);
-    
-    let composer_path = cwd.join("composer.json");
-
-    let home = dirs::home_dir().expect("Cannot find home directory");
-    let furnace_yml = home.join(".furnace.yml");
-    let active_php_version = if let Ok(furnace_yml_content) = fs::read_to_string(&furnace_yml) {
-        if let Ok(yaml) = serde_yaml::from_str::<serde_yaml::Value>(&furnace_yml_content) {
-            yaml.get("php_version").and_then(|v| v.as_str()).map(|s| extract_major_minor(s))
-        } else { None }
-    } else { None };
-    let php_version = active_php_version.unwrap_or_else(|| parse_php_version(&composer_path).map(|s| extract_major_minor(&s)).unwrap_or_else(|| "unknown".to_string()));
-    let site = format!("{}.test", project_name);
+    let repo = php::load_repository(paths)?;
+    let suggestion = constraint
+        .best_line(repo.php.keys().map(String::as_str))
+        .map(str::to_string)
+        .unwrap_or_else(|| extract_major_minor(&requirement));
+    warn!(
+        "No installed PHP satisfies '{}' required by {}; run `furnace php install {}`",
+        requirement,
+        project_dir.display(),
+        suggestion
+    );
+    Ok(suggestion)
+}
 
-    let recipes_dir = home.join(".furnace/recipes");
-    fs::create_dir_all(&recipes_dir).expect("Failed to create recipes dir");
-    let cwd_str = cwd.to_string_lossy();
-    let already_registered = std::fs::read_dir(&recipes_dir)
-        .ok()
-        .and_then(|entries| {
-            for entry in entries.flatten() {
-                if let Ok(content) = fs::read_to_string(entry.path()) {
-                    if let Ok(existing) = serde_yaml::from_str::<Recipe>(&content) {
-                        if existing.path == cwd_str {
-                            error!("A recipe for this directory is already registered as '{}'.", existing.name);
-                            return Some(());
-                        }
-                    }
-                }
-            }
-            None
-        })
-        .is_some();
-    let recipe_filename = format!("{}.yml", project_name);
-    let recipe_path = recipes_dir.join(&recipe_filename);
-    let recipe = Recipe {
-        name: project_name.clone(),
-        path: cwd_str.to_string(),
-        php_version: php_version.clone(),
-        serve_with: "apache".to_string(),
-        site: site.clone(),
-    };
-    let yml = serde_yaml::to_string(&recipe).expect("Failed to serialize recipe");
-    fs::write(&recipe_path, yml).expect("Failed to write recipe file");
-    info!("Recipe created/updated at {}", recipe_path.display());
-    println!("cooking {}", project_name);
+/// The PHP version pinned in the project's `.furnace.yml`, or else the one
+/// resolved from composer.json.
+pub(crate) fn project_php_version(paths: &FurnacePaths, project_dir: &Path) -> Result<String> {
+    match php_version_pin(project_dir) {
+        Some((_, version)) => Ok(version),
+        None => resolve_php_version(paths, project_dir),
+    }
+}
 
-    let recipe: Option<Recipe> = fs::read_to_string(&recipe_path)
+/// The `.furnace.yml` that pins the project's PHP version, and the version.
+///
+/// Earlier releases read a single `php_version` from `~/.furnace.yml` for
+/// every project. That file is still honoured when the project pins nothing,
+/// with a warning, so upgrading doesn't silently change recipes' versions.
+pub(crate) fn php_version_pin(project_dir: &Path) -> Option<(PathBuf, String)> {
+    let project_yml = project_dir.join(".furnace.yml");
+    if let Some(version) = pinned_php_version(&project_yml) {
+        return Some((project_yml, version));
+    }
+    let legacy = dirs::home_dir()?.join(".furnace.yml");
+    let version = pinned_php_version(&legacy)?;
+    warn!(
+        "Using php_version {} from {}; this global setting is deprecated, pin it in {} instead",
+        version,
+        legacy.display(),
+        project_yml.display()
+    );
+    Some((legacy, version))
+}
+
+fn pinned_php_version(furnace_yml: &Path) -> Option<String> {
+    fs::read_to_string(furnace_yml)
         .ok()
-        .and_then(|s| serde_yaml::from_str(&s).ok());
-    let (project_name, cwd_str, php_version, site) = if let Some(recipe) = recipe {
-        (recipe.name, recipe.path, recipe.php_version, recipe.site)
-    } else {
-        (project_name.clone(), cwd_str.to_string(), php_version.clone(), site.clone())
+        .and_then(|content| serde_yaml::from_str::<serde_yaml::Value>(&content).ok())
+        .and_then(|yaml| yaml.get("php_version").and_then(|v| v.as_str()).map(extract_major_minor))
+}
+
+/// Re-reads the project's PHP version and FPM pool settings, then rewrites
+/// the recipe and its vhost and reloads the backend.
+pub fn refresh(paths: &FurnacePaths, name: &str) -> Result<Recipe> {
+    let mut recipe = find_recipe(paths, name)?;
+    let path = PathBuf::from(&recipe.path);
+    recipe.php_version = project_php_version(paths, &path)?;
+    recipe.fpm_pool = php_pool::from_project(&path)?;
+    recipe.orphaned = false;
+    apply(paths, &recipe)?;
+    php_pool::sync(paths, &recipe)?;
+    Ok(recipe)
+}
+
+/// Records whether the recipe's project directory has gone missing.
+pub fn set_orphaned(paths: &FurnacePaths, name: &str, orphaned: bool) -> Result<Recipe> {
+    let mut recipe = find_recipe(paths, name)?;
+    recipe.orphaned = orphaned;
+    fs::write(paths.recipe_file(name), serde_yaml::to_string(&recipe)?)?;
+    Ok(recipe)
+}
+
+/// Registers the project at `path` as a recipe and writes its vhost configs.
+pub fn cook(paths: &FurnacePaths, path: &Path, opts: CookOptions) -> Result<Recipe> {
+    let path = path.canonicalize()?;
+    let project_driver = match &opts.driver {
+        Some(name) => driver::by_name(name)?,
+        None => driver::detect(&path).ok_or_else(|| {
+            FurnaceError::InvalidProject(format!("no project driver recognizes {}", path.display()))
+        })?,
+    };
+    let project_name = match opts.name {
+        Some(name) => name,
+        None => path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| FurnaceError::InvalidProject("cannot derive a project name from /".to_string()))?,
     };
 
-    let apache_dir = home.join(".furnace/apache");
-    fs::create_dir_all(&apache_dir).expect("Failed to create apache dir");
-    let apache_conf_path = apache_dir.join(format!("{}.conf", project_name));
-    let logs_dir = apache_dir.join("logs");
-    fs::create_dir_all(&logs_dir).expect("Failed to create apache logs dir");
-    let php_fpm_socket = format!("{}/.furnace/php/{}/php-fpm.sock", home.to_string_lossy(), php_version);
-    let apache_conf = format!(r#"
-<VirtualHost *:80>
-    ServerName {site}
-    DocumentRoot "{project_path}/public"
-
-    <Directory "{project_path}/public">
-        AllowOverride All
-        Require all granted
-    </Directory>
-
-    <FilesMatch \.php$>
-        SetHandler "proxy:unix:{php_fpm_socket}|fcgi://localhost/"
-    </FilesMatch>
-
-    ErrorLog "{logs_dir}/{project}.error.log"
-    CustomLog "{logs_dir}/{project}.access.log" combined
-</VirtualHost>
-"#,
-        site=site,
-        project_path=cwd_str,
-        php_fpm_socket=php_fpm_socket,
-        logs_dir=logs_dir.to_string_lossy(),
-        project=project_name
-    );
-    fs::write(&apache_conf_path, apache_conf).expect("Failed to write apache conf");
-    info!("Apache config created/updated at {}", apache_conf_path.display());
-
-    let nginx_dir = home.join(".furnace/nginx");
-    fs::create_dir_all(&nginx_dir).expect("Failed to create nginx dir");
-    let nginx_logs_dir = nginx_dir.join("logs");
-    fs::create_dir_all(&nginx_logs_dir).expect("Failed to create nginx logs dir");
-    let nginx_conf = format!(r#"
-server {{
-    listen 80;
-    server_name {site};
-    root {project_path}/public;
-
-    index index.php index.html;
-
-    access_log {logs_dir}/{project}.access.log;
-    error_log {logs_dir}/{project}.error.log;
-
-    location / {{
-        try_files $uri $uri/ /index.php?$query_string;
-    }}
-
-    location ~ \.php$ {{
-        include /opt/homebrew/etc/nginx/fastcgi_params;
-        fastcgi_pass unix:{php_fpm_socket};
-        fastcgi_param SCRIPT_FILENAME $document_root$fastcgi_script_name;
-        fastcgi_index index.php;
-    }}
-}}
-"#,
-        site=site,
-        project_path=cwd_str,
-        logs_dir=nginx_logs_dir.to_string_lossy(),
-        php_fpm_socket=php_fpm_socket,
-        project=project_name
-    );
+    let php_version = project_php_version(paths, &path)?;
+    let site = format!("{}.test", project_name);
+    let serve_with = opts.serve_with.unwrap_or_else(|| "nginx".to_string());
+    Backends::new(paths).get(&serve_with)?;
 
-    let servers_dir = nginx_dir.join("servers");
-    if let Err(e) = fs::create_dir_all(&servers_dir) {
-        error!("Failed to create nginx servers dir: {e}");
+    fs::create_dir_all(paths.recipes_dir())?;
+    let path_str = path.to_string_lossy().to_string();
+    if let Some(existing) = get_recipes(paths)?.into_iter().find(|r| r.path == path_str && r.name != project_name) {
+        warn!("A recipe for this directory is already registered as '{}'.", existing.name);
     }
-    let server_conf_path = servers_dir.join(format!("{}.conf", project_name));
-    if let Err(e) = fs::write(&server_conf_path, &nginx_conf) {
-        error!("Failed to write nginx conf to servers dir: {e}");
-    } else {
-        info!("Nginx config created/updated at {}", server_conf_path.display());
+    let recipe_path = paths.recipe_file(&project_name);
+    let secure = load_recipe(paths, &recipe_path).map(|r| r.secure).unwrap_or(false);
+    let fpm_pool = php_pool::from_project(&path)?;
+    let recipe = Recipe {
+        name: project_name,
+        path: path_str,
+        php_version,
+        serve_with,
+        site,
+        driver: project_driver.name().to_string(),
+        secure,
+        fpm_pool,
+        orphaned: false,
+    };
+    fs::write(&recipe_path, serde_yaml::to_string(&recipe)?)?;
+    info!("Recipe created/updated at {}", recipe_path.display());
+    php_pool::sync(paths, &recipe)?;
+
+    let backends = Backends::new(paths);
+    let backend = backends.for_recipe(&recipe)?;
+    backend.write_conf(&recipe, &FurnaceConfig::load(paths)?)?;
+    info!("{} config created/updated at {}", backend.name(), backend.conf_path(&recipe.name).display());
+    // Drop configs left behind by a previous serve_with
+    for other in backends.iter().filter(|b| b.name() != backend.name()) {
+        if other.remove_conf(&recipe.name)?.is_some()
+            && other.is_running()
+            && let Err(e) = other.reload()
+        {
+            warn!("Failed to reload {}: {e}", other.name());
+        }
+    }
+    if backend.is_running() {
+        backend.test_config()?;
+        if let Err(e) = backend.reload() {
+            warn!("Failed to reload {}: {e}", backend.name());
+        }
     }
 
-    let project_symlink = cwd.join(".furnace.recipe.yml");
+    let project_symlink = path.join(".furnace.recipe.yml");
     if project_symlink.exists() || project_symlink.is_symlink() {
         let _ = fs::remove_file(&project_symlink);
     }
@@ -203,106 +286,146 @@ server {{
             info!("Symlinked recipe to {}", project_symlink.display());
         }
     }
+    sync_dns(paths);
 
-    println!("{} is cooked at http://{}", project_name, site);
+    Ok(recipe)
 }
 
-/// Returns all recipes as a Vec<Recipe> for use in UI or API.
-pub fn get_recipes() -> Vec<Recipe> {
-    let home = dirs::home_dir().expect("Cannot find home directory");
-    let recipes_dir = home.join(".furnace/recipes");
-    let mut recipes = Vec::new();
-    if let Ok(entries) = fs::read_dir(&recipes_dir) {
-        for entry in entries.flatten() {
-            if let Ok(content) = fs::read_to_string(entry.path()) {
-                if let Ok(recipe) = serde_yaml::from_str::<Recipe>(&content) {
-                    recipes.push(recipe);
-                }
-            }
-        }
+/// Keeps the DNS provider's site list current; resolution failing is not
+/// fatal to cooking or disposing.
+fn sync_dns(paths: &FurnacePaths) {
+    if let Err(e) = dns::sync(paths) {
+        warn!("Could not update .{} DNS: {e}", dns::TLD);
     }
-    recipes
 }
 
-pub fn list_recipes() {
-    let recipes = get_recipes();
-    let mut table = prettytable::Table::new();
-    table.add_row(prettytable::row!["Name", "Directory", "Site"]);
-    for recipe in recipes {
-        table.add_row(prettytable::row![recipe.name, recipe.path, recipe.site]);
-    }
-    table.printstd();
+/// Reads a recipe file, rejecting unknown `serve_with` backends.
+pub fn load_recipe(paths: &FurnacePaths, path: &Path) -> Result<Recipe> {
+    let recipe: Recipe = serde_yaml::from_str(&fs::read_to_string(path)?)?;
+    Backends::new(paths).for_recipe(&recipe)?;
+    driver::by_name(&recipe.driver)?;
+    Ok(recipe)
 }
 
+/// Loads the recipe cooked from `dir` via its `.furnace.recipe.yml` symlink.
+pub fn recipe_for_dir(paths: &FurnacePaths, dir: &Path) -> Result<Recipe> {
+    let symlink = dir.join(".furnace.recipe.yml");
+    if !symlink.exists() {
+        return Err(FurnaceError::RecipeNotFound(dir.to_string_lossy().to_string()));
+    }
+    load_recipe(paths, &symlink)
+}
 
-pub enum RecipeDisposalTarget {
-    ByName(String),
-    ByCurrentDir,
+/// Loads a registered recipe by name.
+pub fn find_recipe(paths: &FurnacePaths, name: &str) -> Result<Recipe> {
+    let path = paths.recipe_file(name);
+    if !path.exists() {
+        return Err(FurnaceError::RecipeNotFound(name.to_string()));
+    }
+    load_recipe(paths, &path)
 }
 
-pub fn dispose_recipe(target: RecipeDisposalTarget) {
-    use std::path::PathBuf;
-    let home = dirs::home_dir().expect("Cannot find home directory");
-    let recipes_dir = home.join(".furnace/recipes");
-    let (project_name, recipe_path, nginx_conf_path, symlink_path): (String, PathBuf, PathBuf, Option<PathBuf>);
+/// Returns all recipes as a Vec<Recipe> for use in UI or API.
+/// Recipes that fail to load are logged and skipped.
+pub fn get_recipes(paths: &FurnacePaths) -> Result<Vec<Recipe>> {
+    let mut recipes = Vec::new();
+    if let Ok(entries) = fs::read_dir(paths.recipes_dir()) {
+        for entry in entries.flatten() {
+            match load_recipe(paths, &entry.path()) {
+                Ok(recipe) => recipes.push(recipe),
+                Err(e) => error!("Skipping recipe {}: {e}", entry.path().display()),
+            }
+        }
+    }
+    recipes.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(recipes)
+}
 
-    match target {
+/// Removes a recipe along with its vhost configs and project symlink.
+pub fn dispose(paths: &FurnacePaths, target: RecipeDisposalTarget) -> Result<DisposeReport> {
+    let (project_name, recipe_path, symlink_path) = match target {
         RecipeDisposalTarget::ByName(name) => {
-            project_name = name.clone();
-            recipe_path = recipes_dir.join(format!("{}.yml", &project_name));
-            nginx_conf_path = home.join(".furnace/nginx/servers").join(format!("{}.conf", &project_name));
-            symlink_path = None;
+            let recipe_path = paths.recipe_file(&name);
+            if !recipe_path.exists() {
+                return Err(FurnaceError::RecipeNotFound(name));
+            }
+            (name, recipe_path, None)
         },
-        RecipeDisposalTarget::ByCurrentDir => {
-            let cwd = std::env::current_dir().expect("Failed to get current dir");
-            let symlink = cwd.join(".furnace.recipe.yml");
-            if !symlink.exists() || !symlink.is_symlink() {
-                error!("No recipe found in this directory.");
-                return;
+        RecipeDisposalTarget::ByPath(dir) => {
+            let symlink = dir.join(".furnace.recipe.yml");
+            if !symlink.is_symlink() {
+                return Err(FurnaceError::RecipeNotFound(dir.to_string_lossy().to_string()));
             }
-            let target = match fs::read_link(&symlink) {
-                Ok(path) => path,
-                Err(e) => {
-                    error!("Failed to read symlink: {}", e);
-                    return;
-                }
-            };
-            project_name = target.file_stem().unwrap().to_string_lossy().to_string();
-            recipe_path = target;
-            nginx_conf_path = home.join(".furnace/nginx/servers").join(format!("{}.conf", &project_name));
-            symlink_path = Some(symlink);
+            let target = fs::read_link(&symlink)?;
+            let name = target.file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .ok_or_else(|| FurnaceError::RecipeNotFound(target.to_string_lossy().to_string()))?;
+            (name, target, Some(symlink))
         }
-    }
+    };
 
-    if nginx_conf_path.exists() {
-        if let Err(e) = fs::remove_file(&nginx_conf_path) {
-            error!("Failed to delete nginx config: {}", e);
-        } else {
-            info!("Deleted nginx config at {}", nginx_conf_path.display());
-        }
+    let loaded = load_recipe(paths, &recipe_path).ok();
+    let serve_with = loaded.as_ref().map(|r| r.serve_with.clone());
+    let mut removed = php_pool::remove(paths, &project_name)?;
+    if let Some(recipe) = &loaded {
+        removed.extend(tls::remove_site_cert(paths, &recipe.site)?);
     }
-    if recipe_path.exists() {
-        if let Err(e) = fs::remove_file(&recipe_path) {
-            error!("Failed to delete recipe file: {}", e);
-        } else {
-            info!("Deleted recipe file at {}", recipe_path.display());
+    let backends = Backends::new(paths);
+    for backend in backends.iter() {
+        if let Some(path) = backend.remove_conf(&project_name)? {
+            info!("Deleted {}", path.display());
+            removed.push(path);
         }
     }
-    if let Some(symlink) = symlink_path {
-        if symlink.exists() || symlink.is_symlink() {
-            if let Err(e) = fs::remove_file(&symlink) {
-                error!("Failed to delete symlink: {}", e);
-            } else {
-                info!("Deleted symlink at {}", symlink.display());
-            }
+    for path in [Some(recipe_path), symlink_path].into_iter().flatten() {
+        if path.exists() || path.is_symlink() {
+            fs::remove_file(&path)?;
+            info!("Deleted {}", path.display());
+            removed.push(path);
         }
     }
-    println!("Recipe for '{}' has been disposed.", project_name);
+    if let Some(backend) = serve_with.and_then(|name| backends.get(&name).ok())
+        && backend.is_running()
+        && let Err(e) = backend.reload()
+    {
+        warn!("Failed to reload {} after disposing {}: {e}", backend.name(), project_name);
+    }
+    sync_dns(paths);
+    Ok(DisposeReport { name: project_name, removed })
+}
+
+/// Issues a certificate for the recipe's site and serves it over HTTPS.
+pub fn secure(paths: &FurnacePaths, name: &str) -> Result<SecureReport> {
+    let mut recipe = find_recipe(paths, name)?;
+    let ca_created = tls::ensure_ca(paths)?;
+    let cert = tls::issue_site_cert(paths, &recipe.site)?;
+    recipe.secure = true;
+    apply(paths, &recipe)?;
+    Ok(SecureReport { recipe, cert, ca_created })
+}
+
+/// Serves the recipe over plain HTTP again and deletes its certificate.
+pub fn unsecure(paths: &FurnacePaths, name: &str) -> Result<Recipe> {
+    let mut recipe = find_recipe(paths, name)?;
+    recipe.secure = false;
+    apply(paths, &recipe)?;
+    for path in tls::remove_site_cert(paths, &recipe.site)? {
+        info!("Deleted {}", path.display());
+    }
+    Ok(recipe)
 }
 
-pub fn dispose_recipe_cli(name: Option<String>) {
-    match name {
-        Some(n) => dispose_recipe(RecipeDisposalTarget::ByName(n)),
-        None => dispose_recipe(RecipeDisposalTarget::ByCurrentDir),
+/// Saves a changed recipe and reloads its backend with the new vhost.
+pub(crate) fn apply(paths: &FurnacePaths, recipe: &Recipe) -> Result<()> {
+    fs::write(paths.recipe_file(&recipe.name), serde_yaml::to_string(recipe)?)?;
+    let backends = Backends::new(paths);
+    let backend = backends.for_recipe(recipe)?;
+    backend.write_conf(recipe, &FurnaceConfig::load(paths)?)?;
+    if backend.is_running() {
+        backend.test_config()?;
+        if let Err(e) = backend.reload() {
+            warn!("Failed to reload {}: {e}", backend.name());
+        }
     }
+    Ok(())
 }
@@ -1,385 +1,382 @@
 // Business logic for managing Furnace services (migrated from CLI)
 
 use std::process::Command;
-use std::thread::sleep;
-use std::time::Duration;
-use tracing::info;
+use tracing::{error, info, warn};
+
+use serde::Serialize;
 
 use crate::{
+    config::FurnaceConfig,
+    dns,
+    error::{FurnaceError, Result},
+    paths::FurnacePaths,
+    php_ini,
+    php_installation::{self, PhpInstallation},
+    process::{self, Signal, StopOutcome, StopResult},
     recipe,
-    web_service::{NginxService, WebService},
+    status::{self, FurnaceStatus, ProcessState},
+    supervisor::{self, ProcessSpec, Supervisor},
+    web_service::{ApacheService, Backends, NginxService, WebService},
 };
 
-pub fn stop() {
-    println!("Stopping Furnace services...");
-    let home = match dirs::home_dir() {
-        Some(h) => h,
-        None => {
-            eprintln!("Could not determine home directory");
-            return;
-        }
-    };
-    let nginx_dir = home.join(".furnace/nginx");
-    let nginx_pid = nginx_dir.join("logs/nginx.pid");
-    if nginx_pid.exists() {
-        if let Ok(pid_str) = std::fs::read_to_string(&nginx_pid) {
-            if let Ok(pid) = pid_str.trim().parse::<i32>() {
-                let _ = Command::new("kill")
-                    .arg("-QUIT")
-                    .arg(pid.to_string())
-                    .status();
-                println!("Sent QUIT to Nginx (PID {})", pid);
-                sleep(Duration::from_secs(2));
-            }
+/// Stops nginx, Apache, every PHP-FPM pool and Furnace's DNS, waiting for each to exit and
+/// escalating to TERM and KILL if needed, then removes their runtime files.
+pub fn stop(paths: &FurnacePaths) -> Result<Vec<StopResult>> {
+    info!("Stopping Furnace services...");
+    let mut results = vec![
+        process::stop("nginx", &paths.nginx_pid(), Signal::Quit, &[]),
+        process::stop("apache", &paths.apache_pid(), Signal::Term, &[]),
+    ];
+    for version in php_installation::load_manifest(paths)?.into_keys() {
+        results.push(stop_php_fpm(paths, &version));
+    }
+    match dns::stop(paths) {
+        Ok(result) => results.extend(result),
+        Err(e) => warn!("Could not undo .{} DNS setup: {e}", dns::TLD),
+    }
+    Ok(results)
+}
+
+/// A process listening on a port Furnace wants.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PortHolder {
+    pub pid: u32,
+    pub command: String,
+}
+
+impl std::fmt::Display for PortHolder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (PID {})", self.command, self.pid)
+    }
+}
+
+/// Lists the processes listening on a TCP port, via `lsof`.
+pub fn port_holders(port: u16) -> Result<Vec<PortHolder>> {
+    let output = Command::new("lsof")
+        .arg("-nP")
+        .arg(format!("-iTCP:{}", port))
+        .arg("-sTCP:LISTEN")
+        .output()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => FurnaceError::BinaryNotFound("lsof".to_string()),
+            _ => FurnaceError::Io(e),
+        })?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // lsof also exits non-zero when nothing matches, which is only an error
+    // if it said why
+    if !output.status.success() && stdout.trim().is_empty() && !output.stderr.is_empty() {
+        return Err(FurnaceError::command_failed("lsof", &output));
+    }
+    let mut holders: Vec<PortHolder> = Vec::new();
+    for line in stdout.lines().skip(1) {
+        let cols: Vec<&str> = line.split_whitespace().collect();
+        if let (Some(command), Some(pid)) = (cols.first(), cols.get(1).and_then(|p| p.parse().ok()))
+            && !holders.iter().any(|h| h.pid == pid)
+        {
+            holders.push(PortHolder { pid, command: command.to_string() });
         }
     }
-    let php_dir = home.join(".furnace/php");
-    if let Ok(entries) = std::fs::read_dir(&php_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                let fpm_pid = path.join("php-fpm.pid");
-                if fpm_pid.exists() {
-                    if let Ok(pid_str) = std::fs::read_to_string(&fpm_pid) {
-                        if let Ok(pid) = pid_str.trim().parse::<i32>() {
-                            let _ = Command::new("kill")
-                                .arg("-QUIT")
-                                .arg(pid.to_string())
-                                .status();
-                            println!("Sent QUIT to PHP-FPM (PID {})", pid);
-                            sleep(Duration::from_millis(500));
-                        }
-                    }
-                }
-            }
+    Ok(holders)
+}
+
+/// Fails with the processes holding `port`, or kills them when `force` is set.
+/// Without a working `lsof` it can only tell whether the port is free.
+fn claim_port(port: u16, force: bool) -> Result<()> {
+    let holders = match port_holders(port) {
+        Ok(holders) => holders,
+        Err(e) => {
+            warn!("Cannot list processes on port {}: {e}", port);
+            return match std::net::TcpListener::bind(("0.0.0.0", port)) {
+                Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => Err(FurnaceError::PortInUse(port)),
+                _ => Ok(()),
+            };
         }
+    };
+    if holders.is_empty() {
+        return Ok(());
     }
-    #[cfg(any(target_os = "macos", target_os = "linux"))]
-    {
-        let _ = Command::new("sudo")
-            .arg("brew")
-            .arg("services")
-            .arg("stop")
-            .arg("dnsmasq")
-            .status();
-        println!("Tried to stop dnsmasq via brew services");
+    if !force {
+        return Err(FurnaceError::PortHeld {
+            port,
+            holders: holders.iter().map(|h| h.to_string()).collect::<Vec<_>>().join(", "),
+        });
     }
-    println!("All Furnace services stopped.");
+    for holder in &holders {
+        let outcome = process::escalate(holder.pid, Signal::Term, || !process::alive(holder.pid));
+        warn!("{} holding port {}: {}", holder, port, outcome);
+    }
+    Ok(())
 }
 
-pub fn serve() {
-    #[cfg(any(target_os = "macos", target_os = "linux"))]
-    {
-        let lsof = Command::new("lsof").arg("-i:80").output();
-        if let Ok(output) = lsof {
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            for line in stdout.lines().skip(1) {
-                let cols: Vec<&str> = line.split_whitespace().collect();
-                if let Some(pid) = cols.get(1) {
-                    if let Ok(pid_num) = pid.parse::<i32>() {
-                        let _ = Command::new("kill").arg("-9").arg(pid).status();
-                        eprintln!("Killed process {} using port 80", pid_num);
-                    }
-                }
-            }
+/// Starts PHP-FPM pools, the web servers recipes use and `.test` DNS. Processes
+/// already listening on the configured ports are only killed with `force`.
+pub fn serve(paths: &FurnacePaths, force: bool) -> Result<()> {
+    let config = FurnaceConfig::load(paths)?;
+    let backends = Backends::new(paths);
+    // A running Furnace server is reloaded below, so its ports are expected to be taken
+    if !backends.iter().any(|b| b.is_running()) {
+        claim_port(config.http_port, force)?;
+        if recipe::get_recipes(paths)?.iter().any(|r| r.secure) {
+            claim_port(config.https_port, force)?;
         }
     }
     info!("Starting services...");
-    let home = match dirs::home_dir() {
-        Some(h) => h,
-        None => {
-            eprintln!("Could not determine home directory");
-            return;
+    let mut used = write_recipe_confs(paths, &backends)?;
+    if used.is_empty() {
+        used.push("nginx");
+    }
+    for installation in php_installation::load_manifest(paths)?.values() {
+        if let Err(e) = start_php_fpm(paths, installation) {
+            error!("Failed to start PHP-FPM for {}: {e}", installation.version);
         }
-    };
+    }
 
-    let web_server = NginxService::new();
-    let recipes = recipe::get_recipes();
-    for recipe in recipes {
-        if let Err(e) = web_server.write_conf(&recipe) {
-            eprintln!(
-                "Failed to write Nginx config for recipe {}: {}",
-                recipe.name, e
-            );
-        } else {
-            info!("Nginx config written for recipe {}", recipe.name);
+    if used.len() > 1 {
+        warn!(
+            "Recipes use {}; they all listen on port {} and will conflict",
+            used.join(" and "),
+            config.http_port
+        );
+    }
+    for name in used {
+        start_or_reload(backends.get(name)?)?;
+    }
+    start_dns(paths);
+    Ok(())
+}
+
+/// Starts PHP-FPM and the web servers recipes use under a new [`Supervisor`],
+/// which the caller keeps polling.
+pub fn start_supervised(paths: &FurnacePaths, force: bool) -> Result<Supervisor> {
+    let config = FurnaceConfig::load(paths)?;
+    let backends = Backends::new(paths);
+    // Take over from a `furnace serve` that ran without a supervisor, rather
+    // than finding its web server on our port
+    for result in stop(paths)? {
+        if result.outcome != StopOutcome::NotRunning {
+            info!("{}", result);
         }
     }
-    let php_dir = home.join(".furnace/php");
-    if let Ok(entries) = std::fs::read_dir(&php_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_dir() {
-                let version = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-                let fpm_conf = path.join("furnace-php-fpm.conf");
-                if fpm_conf.exists() {
-                    let socket_path = path.join("php-fpm.sock");
-                    let fpm_running = socket_path.exists();
-                    if fpm_running {
-                        info!(
-                            "PHP-FPM for version {} already running (socket exists)",
-                            version
-                        );
-                        continue;
-                    }
-                    let php_fpm_bin = if cfg!(target_os = "macos") {
-                        format!("/opt/homebrew/opt/php@{}/sbin/php-fpm", version)
-                    } else if cfg!(target_os = "linux") {
-                        format!("/usr/sbin/php-fpm{}", version)
-                    } else {
-                        String::from("php-fpm")
-                    };
-                    let status = Command::new(&php_fpm_bin)
-                        .arg("--nodaemonize")
-                        .arg("--fpm-config")
-                        .arg(&fpm_conf)
-                        .spawn();
-                    match status {
-                        Ok(_) => info!("Started PHP-FPM for version {}", version),
-                        Err(e) => eprintln!("Failed to start PHP-FPM for {}: {e}", version),
-                    }
-                }
+    claim_port(config.http_port, force)?;
+    if recipe::get_recipes(paths)?.iter().any(|r| r.secure) {
+        claim_port(config.https_port, force)?;
+    }
+    let mut used = write_recipe_confs(paths, &backends)?;
+    if used.is_empty() {
+        used.push("nginx");
+    }
+    let mut supervisor = Supervisor::new();
+    let started = (|| {
+        for installation in php_installation::load_manifest(paths)?.values() {
+            let spawned = php_fpm_spec(paths, installation)
+                .and_then(|spec| spec.map_or(Ok(()), |spec| supervisor.spawn(spec)));
+            if let Err(e) = spawned {
+                error!("Failed to start PHP-FPM for {}: {e}", installation.version);
             }
         }
+        for name in &used {
+            let backend = backends.get(name)?;
+            backend.test_config()?;
+            supervisor.spawn(backend.foreground()?)?;
+        }
+        Ok(())
+    })();
+    if let Err(e) = started {
+        supervisor.stop_all();
+        return Err(e);
+    }
+    let provider = dns::provider(paths)?;
+    let dns_started = match provider.server()? {
+        Some(spec) => supervisor.spawn(spec),
+        None => Ok(()),
+    }
+    .and_then(|_| dns::configure(paths, provider.as_ref()));
+    if let Err(e) = dns_started {
+        warn!("Could not set up .{} DNS: {e}", dns::TLD);
     }
-    let home = match dirs::home_dir() {
-        Some(h) => h,
-        None => {
-            eprintln!("Could not determine home directory");
-            return;
+    Ok(supervisor)
+}
+
+/// Sets up `.test` resolution through the configured [`dns`] provider.
+/// Sites stay reachable by IP if it fails, so this only warns.
+fn start_dns(paths: &FurnacePaths) {
+    if let Err(e) = dns::start(paths) {
+        warn!("Could not set up .{} DNS: {e}", dns::TLD);
+    }
+}
+
+/// How to run an installation's FPM master with Furnace's conf.d loaded, or
+/// `None` if its config hasn't been generated. Windows builds only ship
+/// php-cgi, which takes none of FPM's flags, so those are rejected.
+pub fn php_fpm_spec(paths: &FurnacePaths, installation: &PhpInstallation) -> Result<Option<ProcessSpec>> {
+    let version = &installation.version;
+    if installation.fpm.file_stem().is_some_and(|stem| stem == "php-cgi") {
+        return Err(FurnaceError::Config(format!(
+            "PHP {} only provides php-cgi ({}); Furnace can't run it as an FPM pool yet",
+            version,
+            installation.fpm.display()
+        )));
+    }
+    let path = paths.php_version_dir(version);
+    let fpm_conf = path.join("furnace-php-fpm.conf");
+    if !fpm_conf.exists() {
+        return Ok(None);
+    }
+    let mut sockets = vec![paths.php_fpm_socket(version)];
+    sockets.extend(pool_sockets(paths, version));
+    Ok(Some(ProcessSpec {
+        name: format!("php-fpm {}", version),
+        program: installation.fpm.clone(),
+        args: vec!["--nodaemonize".into(), "--fpm-config".into(), fpm_conf.into_os_string()],
+        env: vec![("PHP_INI_SCAN_DIR".to_string(), php_ini::scan_dir_env(paths, version))],
+        pid_file: path.join("php-fpm.pid"),
+        sockets,
+        stop_signal: Signal::Quit,
+    }))
+}
+
+/// Starts the FPM pool for an installation in the background, clearing a
+/// PID file or socket left by a crash. Does nothing if its config hasn't
+/// been generated or it is already up.
+pub fn start_php_fpm(paths: &FurnacePaths, installation: &PhpInstallation) -> Result<()> {
+    let Some(spec) = php_fpm_spec(paths, installation)? else {
+        return Ok(());
+    };
+    match supervisor::clean_stale(&spec) {
+        Err(FurnaceError::AlreadyRunning(_)) => {
+            info!("PHP-FPM for version {} already running", installation.version);
+            return Ok(());
         }
+        result => result?,
     };
-    let nginx_dir = home.join(".furnace/nginx");
-    let nginx_conf = nginx_dir.join("nginx.conf");
-    let nginx_pid = nginx_dir.join("logs/nginx.pid");
-    let status_output = Command::new("nginx")
-        .arg("-p")
-        .arg(&nginx_dir)
-        .arg("-c")
-        .arg("nginx.conf")
-        .arg("-t")
-        .output();
-    match status_output {
-        Ok(output) if output.status.success() => {
-            info!("Nginx config test succeeded (using Furnace config)");
-            let pid_valid = nginx_pid.exists()
-                && std::fs::read_to_string(&nginx_pid)
-                    .map(|s| s.trim().parse::<u32>().is_ok())
-                    .unwrap_or(false);
-            if pid_valid {
-                let reload_output = Command::new("nginx")
-                    .arg("-p")
-                    .arg(&nginx_dir)
-                    .arg("-c")
-                    .arg("nginx.conf")
-                    .arg("-s")
-                    .arg("reload")
-                    .output();
-                match reload_output {
-                    Ok(r) if r.status.success() => info!("Nginx reloaded (using Furnace config)"),
-                    Ok(r) => {
-                        let stderr = String::from_utf8_lossy(&r.stderr);
-                        if stderr.contains("bind() to 0.0.0.0:80 failed")
-                            || stderr.contains("Address already in use")
-                        {
-                            eprintln!(
-                                "Nginx could not start or reload: Port 80 is already in use. Please stop any other web server (like Apache or another Nginx) or change the port in your Furnace config."
-                            );
-                        } else {
-                            eprintln!(
-                                "Nginx reload failed with status: {}. Output: {}",
-                                r.status, stderr
-                            );
-                        }
-                        let start_output = Command::new("nginx")
-                            .arg("-p")
-                            .arg(&nginx_dir)
-                            .arg("-c")
-                            .arg("nginx.conf")
-                            .output();
-                        match start_output {
-                            Ok(s) if s.status.success() => {
-                                info!("Nginx started (using Furnace config)")
-                            }
-                            Ok(s) => {
-                                let stderr = String::from_utf8_lossy(&s.stderr);
-                                if stderr.contains("bind() to 0.0.0.0:80 failed")
-                                    || stderr.contains("Address already in use")
-                                {
-                                    eprintln!(
-                                        "Nginx could not start: Port 80 is already in use. Please stop any other web server (like Apache or another Nginx) or change the port in your Furnace config."
-                                    );
-                                } else {
-                                    eprintln!(
-                                        "Nginx start failed with status: {}. Output: {}",
-                                        s.status, stderr
-                                    );
-                                }
-                            }
-                            Err(e) => eprintln!("Failed to start nginx: {e}"),
-                        }
-                    }
-                    Err(e) => eprintln!("Failed to reload nginx: {e}"),
-                }
-            } else {
-                let start_output = Command::new("nginx")
-                    .arg("-p")
-                    .arg(&nginx_dir)
-                    .arg("-c")
-                    .arg("nginx.conf")
-                    .output();
-                match start_output {
-                    Ok(s) if s.status.success() => info!("Nginx started (using Furnace config)"),
-                    Ok(s) => {
-                        let stderr = String::from_utf8_lossy(&s.stderr);
-                        if stderr.contains("bind() to 0.0.0.0:80 failed")
-                            || stderr.contains("Address already in use")
-                        {
-                            eprintln!(
-                                "Nginx could not start: Port 80 is already in use. Please stop any other web server (like Apache or another Nginx) or change the port in your Furnace config."
-                            );
-                        } else {
-                            eprintln!(
-                                "Nginx start failed with status: {}. Output: {}",
-                                s.status, stderr
-                            );
-                        }
-                    }
-                    Err(e) => eprintln!("Failed to start nginx: {e}"),
-                }
+    Command::new(&spec.program)
+        .args(&spec.args)
+        .envs(spec.env)
+        .spawn()
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                FurnaceError::BinaryNotFound(installation.fpm.to_string_lossy().to_string())
             }
-        }
-        Ok(output) => {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            eprintln!(
-                "Nginx config test failed with status: {}. Output: {}",
-                output.status, stderr
+            _ => FurnaceError::Io(e),
+        })?;
+    info!("Started PHP-FPM for version {}", installation.version);
+    Ok(())
+}
+
+/// Gracefully stops one version's FPM master, waits for it to exit and
+/// removes its PID file and sockets, including ones left by a crash.
+pub fn stop_php_fpm(paths: &FurnacePaths, version: &str) -> StopResult {
+    let mut sockets = vec![paths.php_fpm_socket(version)];
+    sockets.extend(pool_sockets(paths, version));
+    let pid_file = paths.php_version_dir(version).join("php-fpm.pid");
+    process::stop(&format!("php-fpm {}", version), &pid_file, Signal::Quit, &sockets)
+}
+
+fn pool_sockets(paths: &FurnacePaths, version: &str) -> Vec<std::path::PathBuf> {
+    let Ok(entries) = std::fs::read_dir(paths.php_pool_dir(version)) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "sock"))
+        .collect()
+}
+
+/// Reloads one version's FPM pools if they are running, leaving the others.
+/// The master re-reads its config in place (SIGUSR2), so a supervised
+/// master keeps its PID.
+pub fn restart_php_fpm(paths: &FurnacePaths, installation: &PhpInstallation) -> Result<()> {
+    let pid_file = paths.php_version_dir(&installation.version).join("php-fpm.pid");
+    let pool = status::process_status(&pid_file);
+    let Some(pid) = pool.pid.filter(|_| pool.state == ProcessState::Running) else {
+        return Ok(());
+    };
+    process::signal(pid, Signal::Usr2)?;
+    info!("Reloaded PHP-FPM {} (PID {})", installation.version, pid);
+    Ok(())
+}
+
+/// Write every recipe's vhost through its backend, returning the backends in use.
+fn write_recipe_confs(paths: &FurnacePaths, backends: &Backends) -> Result<Vec<&'static str>> {
+    let config = FurnaceConfig::load(paths)?;
+    let mut used = Vec::new();
+    for recipe in recipe::get_recipes(paths)? {
+        let web_server = backends.for_recipe(&recipe)?;
+        if let Err(e) = web_server.write_conf(&recipe, &config) {
+            error!(
+                "Failed to write {} config for recipe {}: {}",
+                web_server.name(), recipe.name, e
             );
-            if stderr.contains("bind() to 0.0.0.0:80 failed")
-                || stderr.contains("Address already in use")
-            {
-                eprintln!(
-                    "Nginx could not start: Port 80 is already in use. Please stop any other web server (like Apache or another Nginx) or change the port in your Furnace config."
-                );
-            }
+        } else {
+            info!("{} config written for recipe {}", web_server.name(), recipe.name);
+        }
+        if !used.contains(&web_server.name()) {
+            used.push(web_server.name());
         }
-        Err(e) => eprintln!("Failed to run nginx: {e}"),
     }
-    let dnsmasq_check = Command::new("which").arg("dnsmasq").output();
-    match dnsmasq_check {
-        Ok(output) if output.status.success() => {
-            info!(
-                "dnsmasq is installed: {}",
-                String::from_utf8_lossy(&output.stdout).trim()
-            );
-            let dnsmasq_dir = home.join(".furnace/dnsmasq.d");
-            if let Err(e) = std::fs::create_dir_all(&dnsmasq_dir) {
-                eprintln!("Failed to create dnsmasq.d dir: {e}");
-            }
-            let conf_path = dnsmasq_dir.join("furnace-test.conf");
-            let conf_content = "address=/.test/127.0.0.1\n";
-            if let Err(e) = std::fs::write(&conf_path, conf_content) {
-                eprintln!("Failed to write dnsmasq config: {e}");
-            } else {
-                info!(
-                    "Wrote dnsmasq config for .test domains to {}",
-                    conf_path.display()
-                );
-            }
-            let reload = Command::new("sudo")
-                .arg("brew")
-                .arg("services")
-                .arg("restart")
-                .arg("dnsmasq")
-                .status();
-            match reload {
-                Ok(r) if r.success() => info!("dnsmasq reloaded (for .test domains)"),
-                Ok(r) => eprintln!("dnsmasq reload failed with status: {}", r),
-                Err(e) => eprintln!("Failed to reload dnsmasq: {e}"),
-            }
-            println!(
-                "If you haven't already, add 'conf-dir={}' to your /usr/local/etc/dnsmasq.conf and restart dnsmasq.",
-                dnsmasq_dir.display()
-            );
-            println!("You may also need to run: sudo brew services restart dnsmasq");
-            println!("And set your system DNS to 127.0.0.1");
-            let main_conf_paths = [
-                "/usr/local/etc/dnsmasq.conf",
-                "/opt/homebrew/etc/dnsmasq.conf",
-                "/etc/dnsmasq.conf",
-            ];
-            let conf_dir_line = format!("conf-dir={}", dnsmasq_dir.display());
-            let mut updated = false;
-            for path in &main_conf_paths {
-                let conf_path = std::path::Path::new(path);
-                if conf_path.exists() {
-                    let content = std::fs::read_to_string(conf_path).unwrap_or_default();
-                    let already_present = content
-                        .lines()
-                        .any(|l| l.trim_start().starts_with(&conf_dir_line));
-                    if !already_present {
-                        let new_content = format!("{}\n{}\n", content.trim_end(), conf_dir_line);
-                        if let Err(e) = std::fs::write(conf_path, new_content) {
-                            eprintln!("Failed to update {}: {e}", conf_path.display());
-                        } else {
-                            info!("Added '{}' to {}", conf_dir_line, conf_path.display());
-                            updated = true;
-                        }
-                    }
-                }
-            }
-            if !updated {
-                println!(
-                    "Please ensure 'conf-dir={}' is present in your dnsmasq.conf",
-                    dnsmasq_dir.display()
-                );
+    Ok(used)
+}
+
+/// Regenerate every recipe's vhost and reload the backends that are running.
+pub fn reload(paths: &FurnacePaths) -> Result<()> {
+    let backends = Backends::new(paths);
+    write_recipe_confs(paths, &backends)?;
+    for backend in backends.iter().filter(|b| b.is_running()) {
+        backend.test_config()?;
+        backend.reload()?;
+        info!("{} reloaded (using Furnace config)", backend.name());
+    }
+    Ok(())
+}
+
+/// Validate the config, then reload a running instance or start a new one.
+fn start_or_reload(web_server: &dyn WebService) -> Result<()> {
+    let name = web_server.name();
+    web_server.test_config()?;
+    info!("{} config test succeeded (using Furnace config)", name);
+    if web_server.is_running() {
+        match web_server.reload() {
+            Ok(()) => {
+                info!("{} reloaded (using Furnace config)", name);
+                return Ok(());
             }
-        }
-        _ => {
-            eprintln!(
-                "Warning: dnsmasq is not installed or not in PATH. .test domains will not resolve to 127.0.0.1"
-            );
+            Err(e) => warn!("{} reload failed, starting it instead: {e}", name),
         }
     }
+    web_server.start()?;
+    info!("{} started (using Furnace config)", name);
+    Ok(())
 }
 
-pub fn install() {
+pub fn install(paths: &FurnacePaths) -> Result<()> {
     info!("Installing services...");
-    let nginx = NginxService::new();
-    let home = match dirs::home_dir() {
-        Some(h) => h,
-        None => {
-            eprintln!("Could not determine home directory");
-            return;
-        }
-    };
-    let furnace_dir = home.join(".furnace");
-    let repo_dst = furnace_dir.join("repository.yml");
-    if let Err(e) = std::fs::create_dir_all(&furnace_dir) {
-        eprintln!("Failed to create ~/.furnace directory: {e}");
-        return;
-    }
-    let repo_src = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets/repository.yml");
-    match std::fs::copy(&repo_src, &repo_dst) {
-        Ok(_) => info!("Copied default repository.yml to {}", repo_dst.display()),
-        Err(e) => eprintln!("Failed to copy repository.yml: {e}"),
-    }
+    std::fs::create_dir_all(paths.root())?;
+    info!("Default configs are built in; run 'furnace assets extract' to customize them");
     if NginxService::detect_installation() {
         info!("Nginx is installed");
     } else {
-        eprintln!(
-            "Warning: Nginx is not installed or not in PATH. Please install it before using 'furnace serve'."
+        warn!(
+            "Nginx is not installed or not in PATH. Please install it before using 'furnace serve'."
         );
     }
+    if ApacheService::detect_installation() {
+        info!("Apache is installed");
+    } else {
+        info!("Apache is not installed; recipes with serve_with: apache will not be served");
+    }
+    Ok(())
 }
 
-pub fn status() {
+pub fn status(paths: &FurnacePaths) -> Result<FurnaceStatus> {
     info!("Checking status...");
-    // TODO: Check status of services
+    status::collect(paths)
 }
 
-pub fn restart() {
+pub fn restart(paths: &FurnacePaths, force: bool) -> Result<()> {
     info!("Restarting Furnace services...");
-    stop();
-    serve();
+    for result in stop(paths)? {
+        info!("{}", result);
+    }
+    serve(paths, force)
 }
@@ -0,0 +1,182 @@
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+
+use crate::dns::{self, DnsStatus};
+use crate::driver;
+use crate::error::Result;
+use crate::paths::FurnacePaths;
+use crate::php_installation;
+use crate::process;
+use crate::recipe::{self, Recipe};
+use crate::web_service::Backends;
+
+/// Snapshot of every Furnace-managed service and recipe.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FurnaceStatus {
+    pub nginx: ProcessStatus,
+    pub apache: ProcessStatus,
+    pub php_pools: Vec<PhpPoolStatus>,
+    pub dns: DnsStatus,
+    pub recipes: Vec<RecipeHealth>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessState {
+    /// PID file exists and the process is alive
+    Running,
+    /// PID file exists but the process is gone
+    Stale,
+    /// No PID file
+    Stopped,
+}
+
+impl std::fmt::Display for ProcessState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ProcessState::Running => "running",
+            ProcessState::Stale => "stale",
+            ProcessState::Stopped => "stopped",
+        };
+        f.write_str(s)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProcessStatus {
+    pub pid_file: String,
+    pub pid: Option<u32>,
+    pub state: ProcessState,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhpPoolStatus {
+    pub version: String,
+    pub process: ProcessStatus,
+    pub socket: String,
+    pub socket_exists: bool,
+    pub responding: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecipeHealth {
+    pub name: String,
+    pub site: String,
+    pub php_version: String,
+    pub serve_with: String,
+    pub secure: bool,
+    pub orphaned: bool,
+    pub healthy: bool,
+    pub issues: Vec<String>,
+}
+
+/// Collects the status of nginx, every PHP-FPM pool, DNS and all recipes.
+pub fn collect(paths: &FurnacePaths) -> Result<FurnaceStatus> {
+    let nginx = process_status(&paths.nginx_pid());
+    let apache = process_status(&paths.apache_pid());
+    let php_pools = php_pools(paths)?;
+    let dns = dns::status(paths)?;
+    let recipes = recipe::get_recipes(paths)?
+        .iter()
+        .map(|r| recipe_health(r, paths, &php_pools))
+        .collect();
+    Ok(FurnaceStatus { nginx, apache, php_pools, dns, recipes })
+}
+
+/// Reads a PID file and checks whether the process it names is still alive.
+pub fn process_status(pid_file: &Path) -> ProcessStatus {
+    let pid = fs::read_to_string(pid_file)
+        .ok()
+        .and_then(|s| s.trim().parse::<u32>().ok());
+    let state = match pid {
+        Some(pid) if process_alive(pid) => ProcessState::Running,
+        Some(_) => ProcessState::Stale,
+        None => ProcessState::Stopped,
+    };
+    ProcessStatus {
+        pid_file: pid_file.to_string_lossy().to_string(),
+        pid,
+        state,
+    }
+}
+
+/// Returns true if a process with the given PID exists.
+pub fn process_alive(pid: u32) -> bool {
+    process::alive(pid)
+}
+
+/// One pool per recorded PHP installation.
+fn php_pools(paths: &FurnacePaths) -> Result<Vec<PhpPoolStatus>> {
+    let mut pools = Vec::new();
+    for version in php_installation::load_manifest(paths)?.into_keys() {
+        let path = paths.php_version_dir(&version);
+        let socket = path.join("php-fpm.sock");
+        let socket_exists = socket.exists();
+        pools.push(PhpPoolStatus {
+            version,
+            process: process_status(&path.join("php-fpm.pid")),
+            socket: socket.to_string_lossy().to_string(),
+            socket_exists,
+            responding: socket_exists && socket_responding(&socket),
+        });
+    }
+    Ok(pools)
+}
+
+fn socket_responding(socket: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        std::os::unix::net::UnixStream::connect(socket).is_ok()
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = socket;
+        false
+    }
+}
+
+fn recipe_health(recipe: &Recipe, paths: &FurnacePaths, pools: &[PhpPoolStatus]) -> RecipeHealth {
+    let mut issues = Vec::new();
+    let project_dir = Path::new(&recipe.path);
+    if recipe.orphaned {
+        issues.push(format!(
+            "orphaned: project directory {} was moved or deleted; cook it again or dispose the recipe",
+            recipe.path
+        ));
+    } else if !project_dir.exists() {
+        issues.push(format!("project directory {} is missing", recipe.path));
+    } else if let Ok(project_driver) = driver::by_name(&recipe.driver) {
+        let root = project_driver.document_root(project_dir);
+        if !root.exists() {
+            issues.push(format!("document root {} is missing", root.display()));
+        }
+    }
+    match pools.iter().find(|p| p.version == recipe.php_version) {
+        None => issues.push(format!("PHP {} is not installed", recipe.php_version)),
+        Some(pool) if !pool.responding => {
+            issues.push(format!("PHP-FPM {} is not responding", recipe.php_version))
+        }
+        Some(_) => {}
+    }
+    if recipe.secure && !paths.site_cert(&recipe.site).exists() {
+        issues.push(format!("certificate for {} is missing", recipe.site));
+    }
+    match Backends::new(paths).for_recipe(recipe) {
+        Ok(backend) if !backend.conf_path(&recipe.name).exists() => {
+            issues.push(format!("{} server config is missing", backend.name()))
+        }
+        Ok(_) => {}
+        Err(e) => issues.push(e.to_string()),
+    }
+    RecipeHealth {
+        name: recipe.name.clone(),
+        site: recipe.site.clone(),
+        php_version: recipe.php_version.clone(),
+        serve_with: recipe.serve_with.clone(),
+        secure: recipe.secure,
+        orphaned: recipe.orphaned,
+        healthy: issues.is_empty(),
+        issues,
+    }
+}
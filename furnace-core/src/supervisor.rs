@@ -0,0 +1,275 @@
+//! Owns Furnace's long-running processes while `furnace serve --foreground`
+//! runs: starts them, notices when they exit and restarts them with backoff.
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::error::{FurnaceError, Result};
+use crate::process::{self, Signal, StopOutcome, StopResult};
+use crate::status::{self, ProcessState};
+
+/// Delay before the first restart; doubled after each crash.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// A process that ran this long before exiting gets the initial backoff again.
+const STABLE_AFTER: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How to run one process in the foreground.
+#[derive(Debug, Clone)]
+pub struct ProcessSpec {
+    pub name: String,
+    pub program: PathBuf,
+    pub args: Vec<OsString>,
+    pub env: Vec<(String, String)>,
+    /// PID file the process writes itself
+    pub pid_file: PathBuf,
+    /// Sockets the process creates, removed when a crash leaves them behind
+    pub sockets: Vec<PathBuf>,
+    /// Signal that asks it to shut down gracefully
+    pub stop_signal: Signal,
+}
+
+/// A supervised process as reported by [`Supervisor::status`].
+#[derive(Serialize, Debug, Clone)]
+pub struct SupervisedStatus {
+    pub name: String,
+    /// `None` while waiting to be restarted
+    pub pid: Option<u32>,
+    pub restarts: u32,
+}
+
+struct Supervised {
+    spec: ProcessSpec,
+    child: Option<Child>,
+    started_at: Instant,
+    restarts: u32,
+    backoff: Duration,
+    restart_at: Option<Instant>,
+}
+
+#[derive(Default)]
+pub struct Supervisor {
+    children: Vec<Supervised>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cleans up after a previous crash and starts the process.
+    pub fn spawn(&mut self, spec: ProcessSpec) -> Result<()> {
+        clean_stale(&spec)?;
+        let child = start(&spec)?;
+        info!("Started {} (PID {})", spec.name, child.id());
+        self.children.push(Supervised {
+            spec,
+            child: Some(child),
+            started_at: Instant::now(),
+            restarts: 0,
+            backoff: INITIAL_BACKOFF,
+            restart_at: None,
+        });
+        Ok(())
+    }
+
+    /// Reaps processes that exited and restarts those whose backoff is over.
+    pub fn poll(&mut self) {
+        let now = Instant::now();
+        for supervised in &mut self.children {
+            if let Some(child) = &mut supervised.child {
+                let exit = match child.try_wait() {
+                    Ok(Some(exit)) => exit.to_string(),
+                    Ok(None) => continue,
+                    Err(e) => e.to_string(),
+                };
+                if now.duration_since(supervised.started_at) >= STABLE_AFTER {
+                    supervised.backoff = INITIAL_BACKOFF;
+                }
+                warn!(
+                    "{} exited ({}); restarting in {}s",
+                    supervised.spec.name,
+                    exit,
+                    supervised.backoff.as_secs()
+                );
+                supervised.child = None;
+                supervised.restart_at = Some(now + supervised.backoff);
+                supervised.backoff = (supervised.backoff * 2).min(MAX_BACKOFF);
+                continue;
+            }
+            if supervised.restart_at.is_some_and(|at| at <= now) {
+                let started = clean_stale(&supervised.spec).and_then(|_| start(&supervised.spec));
+                match started {
+                    Ok(child) => {
+                        supervised.restarts += 1;
+                        info!("Restarted {} (PID {})", supervised.spec.name, child.id());
+                        supervised.child = Some(child);
+                        supervised.started_at = now;
+                        supervised.restart_at = None;
+                    }
+                    Err(e) => {
+                        error!("Failed to restart {}: {e}", supervised.spec.name);
+                        supervised.restart_at = Some(now + supervised.backoff);
+                        supervised.backoff = (supervised.backoff * 2).min(MAX_BACKOFF);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Supervises until `shutdown` is set, then stops everything.
+    pub fn run(&mut self, shutdown: &AtomicBool) {
+        while !shutdown.load(Ordering::SeqCst) {
+            self.poll();
+            sleep(POLL_INTERVAL);
+        }
+        self.stop_all();
+    }
+
+    /// Asks every process to shut down, escalating to TERM and KILL for
+    /// those that don't exit in time, and removes their runtime files.
+    pub fn stop_all(&mut self) -> Vec<StopResult> {
+        self.children.iter_mut().filter_map(stop_child).collect()
+    }
+
+    /// Stops the process called `name` and drops it from supervision, so it
+    /// isn't restarted. `None` if no such process is supervised.
+    pub fn remove(&mut self, name: &str) -> Option<StopResult> {
+        let index = self.children.iter().position(|s| s.spec.name == name)?;
+        let mut supervised = self.children.remove(index);
+        Some(stop_child(&mut supervised).unwrap_or_else(|| StopResult {
+            name: supervised.spec.name.clone(),
+            pid: None,
+            outcome: StopOutcome::NotRunning,
+            removed: clean_stale(&supervised.spec).unwrap_or_default(),
+        }))
+    }
+
+    pub fn status(&self) -> Vec<SupervisedStatus> {
+        self.children
+            .iter()
+            .map(|s| SupervisedStatus {
+                name: s.spec.name.clone(),
+                pid: s.child.as_ref().map(Child::id),
+                restarts: s.restarts,
+            })
+            .collect()
+    }
+}
+
+/// Stops a supervised child through [`process::escalate`], reaping it so it
+/// isn't mistaken for a live process, and removes its runtime files.
+fn stop_child(supervised: &mut Supervised) -> Option<StopResult> {
+    let mut child = supervised.child.take()?;
+    let pid = child.id();
+    let outcome = process::escalate(pid, supervised.spec.stop_signal, || {
+        matches!(child.try_wait(), Ok(Some(_)) | Err(_))
+    });
+    info!("{} (PID {}): {}", supervised.spec.name, pid, outcome);
+    let removed = clean_stale(&supervised.spec).unwrap_or_default();
+    Some(StopResult { name: supervised.spec.name.clone(), pid: Some(pid), outcome, removed })
+}
+
+/// Removes a PID file whose process is gone and sockets nobody serves.
+/// Fails if the PID file names a live process.
+pub fn clean_stale(spec: &ProcessSpec) -> Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    match status::process_status(&spec.pid_file).state {
+        ProcessState::Running => return Err(FurnaceError::AlreadyRunning(spec.name.clone())),
+        ProcessState::Stale => {
+            fs::remove_file(&spec.pid_file)?;
+            removed.push(spec.pid_file.clone());
+        }
+        ProcessState::Stopped => {}
+    }
+    for socket in &spec.sockets {
+        if socket.exists() {
+            fs::remove_file(socket)?;
+            removed.push(socket.clone());
+        }
+    }
+    for path in &removed {
+        info!("Removed stale {}", path.display());
+    }
+    Ok(removed)
+}
+
+pub(crate) fn start(spec: &ProcessSpec) -> Result<Child> {
+    let mut command = Command::new(&spec.program);
+    command.args(&spec.args).envs(spec.env.iter().map(|(k, v)| (k, v)));
+    // Keep Ctrl-C in the terminal from reaching children directly; the
+    // supervisor stops them itself
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    command.spawn().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => FurnaceError::BinaryNotFound(spec.program.to_string_lossy().to_string()),
+        _ => FurnaceError::Io(e),
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn spec(dir: &std::path::Path, script: &str) -> ProcessSpec {
+        ProcessSpec {
+            name: "test".into(),
+            program: "sh".into(),
+            args: vec!["-c".into(), script.into()],
+            env: Vec::new(),
+            pid_file: dir.join("test.pid"),
+            sockets: vec![dir.join("test.sock")],
+            stop_signal: Signal::Term,
+        }
+    }
+
+    #[test]
+    fn stale_files_are_removed_and_live_ones_refused() {
+        let tmp = tempfile::tempdir().unwrap();
+        let spec = spec(tmp.path(), "true");
+        fs::write(&spec.pid_file, "999999999").unwrap();
+        fs::write(&spec.sockets[0], "").unwrap();
+        assert_eq!(clean_stale(&spec).unwrap().len(), 2);
+
+        fs::write(&spec.pid_file, std::process::id().to_string()).unwrap();
+        assert!(matches!(clean_stale(&spec), Err(FurnaceError::AlreadyRunning(_))));
+    }
+
+    #[test]
+    fn crashed_process_is_restarted() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut supervisor = Supervisor::new();
+        supervisor.spawn(spec(tmp.path(), "exit 1")).unwrap();
+        sleep(Duration::from_millis(200));
+        supervisor.poll();
+        assert_eq!(supervisor.status()[0].pid, None);
+        supervisor.children[0].restart_at = Some(Instant::now());
+        supervisor.children[0].spec.args[1] = "sleep 30".into();
+        supervisor.poll();
+        let status = &supervisor.status()[0];
+        assert!(status.pid.is_some());
+        assert_eq!(status.restarts, 1);
+        supervisor.stop_all();
+        assert_eq!(supervisor.status()[0].pid, None);
+    }
+
+    #[test]
+    fn removed_process_is_stopped_and_forgotten() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut supervisor = Supervisor::new();
+        supervisor.spawn(spec(tmp.path(), "sleep 30")).unwrap();
+        let stopped = supervisor.remove("test").unwrap();
+        assert_eq!(stopped.outcome, StopOutcome::Stopped);
+        assert!(supervisor.status().is_empty());
+        assert!(supervisor.remove("test").is_none());
+    }
+}
@@ -0,0 +1,233 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::FurnaceConfig;
+use crate::driver;
+use crate::error::Result;
+use crate::paths::FurnacePaths;
+use crate::php_pool;
+use crate::recipe::Recipe;
+
+/// Config templates Furnace renders; users may override each one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Template {
+    NginxConf,
+    FastcgiParams,
+    PhpFpmConf,
+    /// Per-project pool included by the version's FPM config
+    PhpFpmPool,
+    NginxVhost,
+    ApacheVhost,
+    /// HTTPS server block appended for secured recipes
+    NginxSecureVhost,
+    ApacheSecureVhost,
+    /// Furnace's own dnsmasq, used by the dnsmasq and resolved DNS providers
+    DnsmasqConf,
+}
+
+impl Template {
+    pub const ALL: [Template; 9] = [
+        Template::NginxConf,
+        Template::FastcgiParams,
+        Template::PhpFpmConf,
+        Template::PhpFpmPool,
+        Template::NginxVhost,
+        Template::ApacheVhost,
+        Template::NginxSecureVhost,
+        Template::ApacheSecureVhost,
+        Template::DnsmasqConf,
+    ];
+
+    /// File name looked up in the override directories.
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Template::NginxConf => "nginx.conf.tpl",
+            Template::FastcgiParams => "fastcgi_params",
+            Template::PhpFpmConf => "php-fpm.conf.tpl",
+            Template::PhpFpmPool => "php-fpm-pool.conf.tpl",
+            Template::NginxVhost => "nginx-vhost.conf.tpl",
+            Template::ApacheVhost => "apache-vhost.conf.tpl",
+            Template::NginxSecureVhost => "nginx-vhost-secure.conf.tpl",
+            Template::ApacheSecureVhost => "apache-vhost-secure.conf.tpl",
+            Template::DnsmasqConf => "dnsmasq.conf.tpl",
+        }
+    }
+
+    /// The template bundled with Furnace.
+    pub fn bundled(&self) -> &'static str {
+        match self {
+            Template::NginxConf => include_str!("../assets/nginx/nginx.conf.tpl"),
+            Template::FastcgiParams => include_str!("../assets/nginx/fastcgi_params"),
+            Template::PhpFpmConf => include_str!("../assets/php-fpm/php-fpm.conf.tpl"),
+            Template::PhpFpmPool => include_str!("../assets/php-fpm/php-fpm-pool.conf.tpl"),
+            Template::NginxVhost => include_str!("../assets/templates/nginx-vhost.conf.tpl"),
+            Template::ApacheVhost => include_str!("../assets/templates/apache-vhost.conf.tpl"),
+            Template::NginxSecureVhost => include_str!("../assets/templates/nginx-vhost-secure.conf.tpl"),
+            Template::ApacheSecureVhost => include_str!("../assets/templates/apache-vhost-secure.conf.tpl"),
+            Template::DnsmasqConf => include_str!("../assets/dnsmasq/dnsmasq.conf.tpl"),
+        }
+    }
+
+    /// The HTTPS companion rendered after this template for secured recipes.
+    pub fn secure(&self) -> Option<Template> {
+        match self {
+            Template::NginxVhost => Some(Template::NginxSecureVhost),
+            Template::ApacheVhost => Some(Template::ApacheSecureVhost),
+            _ => None,
+        }
+    }
+}
+
+/// Directory holding per-project template overrides.
+pub fn project_templates_dir(project_dir: &Path) -> PathBuf {
+    project_dir.join(".furnace/templates")
+}
+
+/// Returns the template source, preferring a per-project override, then a
+/// global override in `~/.furnace/templates`, then the bundled default.
+pub fn source(paths: &FurnacePaths, project_dir: Option<&Path>, template: Template) -> Result<String> {
+    let candidates = project_dir
+        .map(project_templates_dir)
+        .into_iter()
+        .chain(std::iter::once(paths.templates_dir()))
+        .map(|dir| dir.join(template.file_name()));
+    for candidate in candidates {
+        if candidate.exists() {
+            return Ok(fs::read_to_string(candidate)?);
+        }
+    }
+    Ok(template.bundled().to_string())
+}
+
+/// Replaces each `{key}` with its value. Unknown placeholders are left as-is
+/// so config braces survive.
+pub fn render_str(src: &str, vars: &[(&str, String)]) -> String {
+    vars.iter()
+        .fold(src.to_string(), |out, (key, value)| out.replace(&format!("{{{}}}", key), value))
+}
+
+/// Renders a vhost template for a recipe, followed by its HTTPS companion
+/// when the recipe is secured.
+pub fn render_vhost(
+    paths: &FurnacePaths,
+    config: &FurnaceConfig,
+    template: Template,
+    recipe: &Recipe,
+    logs_dir: &Path,
+) -> Result<String> {
+    let project_dir = Path::new(&recipe.path);
+    let vars = vhost_vars(paths, config, recipe, logs_dir)?;
+    let mut out = render_str(&source(paths, Some(project_dir), template)?, &vars);
+    if recipe.secure
+        && let Some(secure) = template.secure()
+    {
+        out.push_str(&render_str(&source(paths, Some(project_dir), secure)?, &vars));
+    }
+    Ok(out)
+}
+
+/// Variables available to vhost templates.
+pub fn vhost_vars(
+    paths: &FurnacePaths,
+    config: &FurnaceConfig,
+    recipe: &Recipe,
+    logs_dir: &Path,
+) -> Result<Vec<(&'static str, String)>> {
+    let project_driver = driver::by_name(&recipe.driver)?;
+    let root = project_driver.document_root(Path::new(&recipe.path));
+    let (index, try_files, fallback) = match project_driver.front_controller() {
+        Some(front) => (
+            format!("{} index.html", front),
+            format!("$uri $uri/ /{}?$query_string", front),
+            format!("FallbackResource /{}", front),
+        ),
+        None => ("index.html index.htm".to_string(), "$uri $uri/ =404".to_string(), String::new()),
+    };
+    Ok(vec![
+        ("name", recipe.name.clone()),
+        ("site", recipe.site.clone()),
+        ("path", recipe.path.clone()),
+        ("root", root.to_string_lossy().to_string()),
+        ("socket", php_pool::socket(paths, recipe).to_string_lossy().to_string()),
+        ("logs_dir", logs_dir.to_string_lossy().to_string()),
+        ("port", config.http_port.to_string()),
+        ("https_port", config.https_port.to_string()),
+        ("cert", paths.site_cert(&recipe.site).to_string_lossy().to_string()),
+        ("cert_key", paths.site_key(&recipe.site).to_string_lossy().to_string()),
+        ("fastcgi_params", paths.nginx_dir().join("fastcgi_params").to_string_lossy().to_string()),
+        ("index", index),
+        ("try_files", try_files),
+        ("locations", project_driver.nginx_locations().to_string()),
+        ("fallback", fallback),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recipe(path: &Path) -> Recipe {
+        Recipe {
+            name: "shop".into(),
+            path: path.to_string_lossy().to_string(),
+            php_version: "8.3".into(),
+            serve_with: "nginx".into(),
+            site: "shop.test".into(),
+            driver: "laravel".into(),
+            secure: false,
+            fpm_pool: None,
+            orphaned: false,
+        }
+    }
+
+    #[test]
+    fn render_keeps_config_braces() {
+        let out = render_str("server { listen {port}; {other} }", &[("port", "80".into())]);
+        assert_eq!(out, "server { listen 80; {other} }");
+    }
+
+    #[test]
+    fn bundled_vhost_has_no_unrendered_vars() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path().join("home"));
+        let recipe = recipe(&tmp.path().join("shop"));
+        let logs = paths.nginx_logs_dir();
+        let out = render_vhost(&paths, &FurnaceConfig::default(), Template::NginxVhost, &recipe, &logs).unwrap();
+        assert!(out.contains("server_name shop.test;"));
+        assert!(out.contains("8.3/php-fpm.sock"));
+        for (key, _) in vhost_vars(&paths, &FurnaceConfig::default(), &recipe, &logs).unwrap() {
+            assert!(!out.contains(&format!("{{{}}}", key)));
+        }
+    }
+
+    #[test]
+    fn secure_recipe_gets_https_block() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path().join("home"));
+        let mut recipe = recipe(&tmp.path().join("shop"));
+        let logs = paths.apache_logs_dir();
+        let plain = render_vhost(&paths, &FurnaceConfig::default(), Template::ApacheVhost, &recipe, &logs).unwrap();
+        assert!(!plain.contains("SSLEngine"));
+
+        recipe.secure = true;
+        let secure = render_vhost(&paths, &FurnaceConfig::default(), Template::ApacheVhost, &recipe, &logs).unwrap();
+        assert!(secure.starts_with(&plain));
+        assert!(secure.contains("<VirtualHost *:443>"));
+        assert!(secure.contains("certs/shop.test.crt"));
+    }
+
+    #[test]
+    fn project_override_beats_global() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path().join("home"));
+        let project = tmp.path().join("shop");
+        fs::create_dir_all(paths.templates_dir()).unwrap();
+        fs::write(paths.templates_dir().join("nginx-vhost.conf.tpl"), "global {site}").unwrap();
+        assert_eq!(source(&paths, Some(&project), Template::NginxVhost).unwrap(), "global {site}");
+
+        fs::create_dir_all(project_templates_dir(&project)).unwrap();
+        fs::write(project_templates_dir(&project).join("nginx-vhost.conf.tpl"), "project {site}").unwrap();
+        let out = render_vhost(&paths, &FurnaceConfig::default(), Template::NginxVhost, &recipe(&project), &paths.nginx_logs_dir()).unwrap();
+        assert_eq!(out, "project shop.test");
+    }
+}
@@ -0,0 +1,138 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Days, NaiveDate, Utc};
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, DnType, ExtendedKeyUsagePurpose, IsCa, KeyPair,
+    KeyUsagePurpose,
+};
+
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+
+const CA_NAME: &str = "Furnace Local CA";
+/// Longest leaf lifetime macOS accepts from a user-trusted root
+const SITE_CERT_DAYS: u64 = 825;
+const CA_CERT_DAYS: u64 = 3650;
+
+impl From<rcgen::Error> for FurnaceError {
+    fn from(e: rcgen::Error) -> Self {
+        FurnaceError::Certificate(e.to_string())
+    }
+}
+
+/// Creates the local root CA under `~/.furnace/ca` unless it already exists.
+/// Returns true if a new CA was generated (and so still needs trusting).
+pub fn ensure_ca(paths: &FurnacePaths) -> Result<bool> {
+    if paths.ca_cert().exists() && paths.ca_key().exists() {
+        return Ok(false);
+    }
+    fs::create_dir_all(paths.ca_dir())?;
+    let key = KeyPair::generate()?;
+    let mut params = ca_params();
+    set_validity(&mut params, CA_CERT_DAYS);
+    let cert = params.self_signed(&key)?;
+    write_key(&paths.ca_key(), &key.serialize_pem())?;
+    fs::write(paths.ca_cert(), cert.pem())?;
+    Ok(true)
+}
+
+/// Issues a certificate for `site` (and its subdomains) signed by the local CA.
+pub fn issue_site_cert(paths: &FurnacePaths, site: &str) -> Result<PathBuf> {
+    let ca_key = KeyPair::from_pem(&fs::read_to_string(paths.ca_key())?)?;
+    let ca = ca_issuer(&ca_key)?;
+
+    let key = KeyPair::generate()?;
+    let mut params = CertificateParams::new(vec![site.to_string(), format!("*.{}", site)])?;
+    params.distinguished_name.push(DnType::CommonName, site);
+    params.key_usages = vec![KeyUsagePurpose::DigitalSignature, KeyUsagePurpose::KeyEncipherment];
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+    set_validity(&mut params, SITE_CERT_DAYS);
+    let cert = params.signed_by(&key, &ca, &ca_key)?;
+
+    fs::create_dir_all(paths.certs_dir())?;
+    write_key(&paths.site_key(site), &key.serialize_pem())?;
+    let cert_path = paths.site_cert(site);
+    fs::write(&cert_path, cert.pem())?;
+    Ok(cert_path)
+}
+
+/// Deletes a site's certificate and key, returning the files removed.
+pub fn remove_site_cert(paths: &FurnacePaths, site: &str) -> Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    for path in [paths.site_cert(site), paths.site_key(site)] {
+        if path.exists() {
+            fs::remove_file(&path)?;
+            removed.push(path);
+        }
+    }
+    Ok(removed)
+}
+
+/// Command that adds the Furnace CA to the system trust store. Furnace never
+/// runs this itself since it needs admin rights.
+pub fn trust_command(paths: &FurnacePaths) -> String {
+    let ca = paths.ca_cert();
+    let ca = ca.to_string_lossy();
+    if cfg!(target_os = "macos") {
+        format!("sudo security add-trusted-cert -d -r trustRoot -k /Library/Keychains/System.keychain \"{}\"", ca)
+    } else if cfg!(target_os = "windows") {
+        format!("certutil -addstore -f Root \"{}\"", ca)
+    } else {
+        format!(
+            "sudo cp \"{}\" /usr/local/share/ca-certificates/furnace-ca.crt && sudo update-ca-certificates",
+            ca
+        )
+    }
+}
+
+fn ca_params() -> CertificateParams {
+    let mut params = CertificateParams::default();
+    params.distinguished_name.push(DnType::CommonName, CA_NAME);
+    params.distinguished_name.push(DnType::OrganizationName, "Furnace");
+    params.is_ca = IsCa::Ca(BasicConstraints::Constrained(0));
+    params.key_usages = vec![KeyUsagePurpose::KeyCertSign, KeyUsagePurpose::CrlSign];
+    params
+}
+
+/// Rebuilds the CA certificate from its key; only the subject and key are
+/// used when signing, so it matches the one written by [`ensure_ca`].
+fn ca_issuer(ca_key: &KeyPair) -> Result<Certificate> {
+    Ok(ca_params().self_signed(ca_key)?)
+}
+
+/// Valid from yesterday, to tolerate clock skew, for `days` days.
+fn set_validity(params: &mut CertificateParams, days: u64) {
+    let ymd = |day: NaiveDate| rcgen::date_time_ymd(day.year(), day.month() as u8, day.day() as u8);
+    let today = Utc::now().date_naive();
+    params.not_before = ymd(today - Days::new(1));
+    params.not_after = ymd(today + Days::new(days));
+}
+
+fn write_key(path: &Path, pem: &str) -> Result<()> {
+    fs::write(path, pem)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn issues_site_cert_from_local_ca() {
+        let tmp = tempfile::tempdir().unwrap();
+        let paths = FurnacePaths::new(tmp.path());
+        assert!(ensure_ca(&paths).unwrap());
+        assert!(!ensure_ca(&paths).unwrap());
+
+        let cert = issue_site_cert(&paths, "shop.test").unwrap();
+        assert!(fs::read_to_string(cert).unwrap().starts_with("-----BEGIN CERTIFICATE-----"));
+        assert!(paths.site_key("shop.test").exists());
+        assert_eq!(remove_site_cert(&paths, "shop.test").unwrap().len(), 2);
+    }
+}
@@ -1,34 +1,162 @@
-use crate::recipe::Recipe;
-use std::process::Command;
+use crate::config::FurnaceConfig;
+use crate::error::{FurnaceError, Result};
+use crate::paths::FurnacePaths;
+use crate::recipe::{self, Recipe};
+use crate::status::{self, ProcessState};
+use crate::process::{self, Signal};
+use crate::supervisor::ProcessSpec;
+use crate::template::{self, Template};
+
+use std::process::{Command, Output};
 use std::fs;
 use std::path::PathBuf;
 
 pub trait WebService {
+    /// Short name matching `Recipe.serve_with`
+    fn name(&self) -> &'static str;
     /// Returns true if the web service is installed on the system
     fn detect_installation() -> bool where Self: Sized;
+    /// Returns true if the Furnace-managed instance is running
+    fn is_running(&self) -> bool;
+    /// Validate the generated config
+    fn test_config(&self) -> Result<()> { Ok(()) }
     /// Start the web service
-    fn start(&self) -> Result<(), String>;
+    fn start(&self) -> Result<()>;
     /// Stop the web service
-    fn stop(&self) -> Result<(), String>;
+    fn stop(&self) -> Result<()>;
+    /// Path of the vhost config for a recipe
+    fn conf_path(&self, recipe_name: &str) -> PathBuf;
     /// Write the config for a given project/recipe
-    fn write_conf(&self, recipe: &Recipe) -> Result<(), String>;
+    fn write_conf(&self, recipe: &Recipe, config: &FurnaceConfig) -> Result<()>;
+    /// Remove a recipe's config, returning its path if it existed
+    fn remove_conf(&self, recipe_name: &str) -> Result<Option<PathBuf>> {
+        let path = self.conf_path(recipe_name);
+        if path.exists() {
+            fs::remove_file(&path)?;
+            Ok(Some(path))
+        } else {
+            Ok(None)
+        }
+    }
     /// Reload the web service (if supported)
-    fn reload(&self) -> Result<(), String> { Ok(()) }
+    fn reload(&self) -> Result<()> { Ok(()) }
+    /// How to run it attached, for the supervisor; writes its main config
+    fn foreground(&self) -> Result<ProcessSpec>;
+}
+
+/// Registry of web server backends keyed by `Recipe.serve_with`.
+pub struct Backends {
+    backends: Vec<Box<dyn WebService>>,
+}
+
+impl Backends {
+    pub fn new(paths: &FurnacePaths) -> Self {
+        Backends {
+            backends: vec![
+                Box::new(NginxService::new(paths)),
+                Box::new(ApacheService::new(paths)),
+            ],
+        }
+    }
+
+    /// Look up a backend by its `serve_with` name.
+    pub fn get(&self, name: &str) -> Result<&dyn WebService> {
+        self.backends
+            .iter()
+            .find(|b| b.name() == name)
+            .map(|b| b.as_ref())
+            .ok_or_else(|| FurnaceError::UnknownBackend(name.to_string()))
+    }
+
+    /// The backend a recipe is served with.
+    pub fn for_recipe(&self, recipe: &Recipe) -> Result<&dyn WebService> {
+        self.get(&recipe.serve_with)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn WebService> {
+        self.backends.iter().map(|b| b.as_ref())
+    }
 }
 
 pub struct NginxService {
     pub nginx_dir: PathBuf,
+    paths: FurnacePaths,
 }
 
 impl NginxService {
-    pub fn new() -> Self {
-        let home = dirs::home_dir().expect("Cannot find home directory");
-        let nginx_dir = home.join(".furnace/nginx");
-        NginxService { nginx_dir }
+    pub fn new(paths: &FurnacePaths) -> Self {
+        NginxService { nginx_dir: paths.nginx_dir(), paths: paths.clone() }
+    }
+
+    /// Run nginx against the Furnace prefix directory with extra arguments.
+    fn run(&self, args: &[&str]) -> Result<Output> {
+        Command::new("nginx")
+            .arg("-p").arg(&self.nginx_dir)
+            .arg("-c").arg("nginx.conf")
+            .args(args)
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => FurnaceError::BinaryNotFound("nginx".to_string()),
+                _ => FurnaceError::Io(e),
+            })
+    }
+
+    pub fn main_conf_path(&self) -> PathBuf {
+        self.nginx_dir.join("nginx.conf")
+    }
+
+    /// Write nginx.conf and fastcgi_params from their templates.
+    pub fn write_main_conf(&self) -> Result<()> {
+        let logs_dir = self.paths.nginx_logs_dir();
+        fs::create_dir_all(&logs_dir)?;
+        fs::create_dir_all(self.paths.nginx_servers_dir())?;
+        let mime_types = NGINX_MIME_TYPES_FILES
+            .iter()
+            .find(|f| std::path::Path::new(f).exists())
+            .map(|f| format!("include {};", f))
+            .unwrap_or_default();
+        let vars = [
+            ("pid_file", self.paths.nginx_pid().to_string_lossy().to_string()),
+            ("logs_dir", logs_dir.to_string_lossy().to_string()),
+            ("mime_types", mime_types),
+        ];
+        let conf = template::source(&self.paths, None, Template::NginxConf)?;
+        fs::write(self.main_conf_path(), template::render_str(&conf, &vars))?;
+        let fastcgi_params = template::source(&self.paths, None, Template::FastcgiParams)?;
+        fs::write(self.nginx_dir.join("fastcgi_params"), fastcgi_params)?;
+        Ok(())
+    }
+}
+
+const NGINX_MIME_TYPES_FILES: &[&str] = &[
+    "/opt/homebrew/etc/nginx/mime.types",
+    "/usr/local/etc/nginx/mime.types",
+    "/etc/nginx/mime.types",
+];
+
+/// Classify a failed nginx invocation, detecting port conflicts.
+fn nginx_error(output: &Output) -> FurnaceError {
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if stderr.contains("Address already in use") {
+        let port = stderr
+            .split("bind() to ")
+            .nth(1)
+            .and_then(|rest| rest.split(' ').next())
+            .and_then(|addr| addr.rsplit(':').next())
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(80);
+        FurnaceError::PortInUse(port)
+    } else if stderr.contains("test failed") {
+        FurnaceError::ConfigValidation { service: "nginx".to_string(), stderr }
+    } else {
+        FurnaceError::command_failed("nginx", output)
     }
 }
 
 impl WebService for NginxService {
+    fn name(&self) -> &'static str {
+        "nginx"
+    }
     fn detect_installation() -> bool {
         #[cfg(target_os = "windows")]
         let output = Command::new("where").arg("nginx.exe").output();
@@ -36,120 +164,313 @@ impl WebService for NginxService {
         let output = Command::new("which").arg("nginx").output();
         matches!(output, Ok(ref o) if o.status.success())
     }
-    fn start(&self) -> Result<(), String> {
-        let status = Command::new("nginx")
-            .arg("-p").arg(&self.nginx_dir)
-            .arg("-c").arg("nginx.conf")
-            .status()
-            .map_err(|e| format!("Failed to start nginx: {e}"))?;
-        if status.success() {
+    fn is_running(&self) -> bool {
+        status::process_status(&self.paths.nginx_pid()).state == ProcessState::Running
+    }
+    /// Validate the Furnace nginx.conf with `nginx -t`.
+    fn test_config(&self) -> Result<()> {
+        let output = self.run(&["-t"])?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(nginx_error(&output))
+        }
+    }
+    fn start(&self) -> Result<()> {
+        self.write_main_conf()?;
+        let output = self.run(&[])?;
+        if output.status.success() {
             Ok(())
         } else {
-            Err(format!("nginx start failed with status: {}", status))
+            Err(nginx_error(&output))
         }
     }
-    fn stop(&self) -> Result<(), String> {
-        let pid_path = self.nginx_dir.join("logs/nginx.pid");
+    fn stop(&self) -> Result<()> {
+        let pid_path = self.paths.nginx_pid();
         if !pid_path.exists() {
             return Ok(()); // Already stopped
         }
-        let pid = fs::read_to_string(&pid_path)
-            .map_err(|e| format!("Failed to read nginx pid: {e}"))?
-            .trim().to_string();
-        let status = Command::new("kill")
-            .arg("-QUIT")
-            .arg(&pid)
-            .status()
-            .map_err(|e| format!("Failed to stop nginx: {e}"))?;
-        if status.success() {
+        let pid = fs::read_to_string(&pid_path)?
+            .trim()
+            .parse()
+            .map_err(|_| FurnaceError::Config(format!("invalid PID file {}", pid_path.display())))?;
+        process::signal(pid, Signal::Quit)
+    }
+    fn conf_path(&self, recipe_name: &str) -> PathBuf {
+        self.paths.nginx_servers_dir().join(format!("{}.conf", recipe_name))
+    }
+    fn write_conf(&self, recipe: &Recipe, config: &FurnaceConfig) -> Result<()> {
+        fs::create_dir_all(self.paths.nginx_servers_dir())?;
+        let logs_dir = self.paths.nginx_logs_dir();
+        fs::create_dir_all(&logs_dir)?;
+        let nginx_conf = template::render_vhost(&self.paths, config, Template::NginxVhost, recipe, &logs_dir)?;
+        fs::write(self.conf_path(&recipe.name), nginx_conf)?;
+        Ok(())
+    }
+    fn reload(&self) -> Result<()> {
+        let output = self.run(&["-s", "reload"])?;
+        if output.status.success() {
             Ok(())
         } else {
-            Err(format!("nginx stop failed with status: {}", status))
+            Err(nginx_error(&output))
         }
     }
-    fn write_conf(&self, recipe: &Recipe) -> Result<(), String> {
-        let servers_dir = self.nginx_dir.join("servers");
-        fs::create_dir_all(&servers_dir)
-            .map_err(|e| format!("Failed to create nginx servers dir: {e}"))?;
-        let conf_path = servers_dir.join(format!("{}.conf", recipe.name));
-        let logs_dir = self.nginx_dir.join("logs");
-        fs::create_dir_all(&logs_dir)
-            .map_err(|e| format!("Failed to create nginx logs dir: {e}"))?;
-        let php_fpm_socket = format!("{}/.furnace/php/{}/php-fpm.sock", dirs::home_dir().unwrap().to_string_lossy(), recipe.php_version);
-        let nginx_conf = format!(r#"
-server {{
-    listen 80;
-    server_name {site};
-    root {project_path}/public;
-
-    index index.php index.html;
-
-    access_log {logs_dir}/{project}.access.log;
-    error_log {logs_dir}/{project}.error.log;
-
-    location / {{
-        try_files $uri $uri/ /index.php?$query_string;
-    }}
-
-    location ~ \.php$ {{
-        include /opt/homebrew/etc/nginx/fastcgi_params;
-        fastcgi_pass unix:{php_fpm_socket};
-        fastcgi_param SCRIPT_FILENAME $document_root$fastcgi_script_name;
-        fastcgi_index index.php;
-    }}
-}}
+    fn foreground(&self) -> Result<ProcessSpec> {
+        self.write_main_conf()?;
+        Ok(ProcessSpec {
+            name: "nginx".to_string(),
+            program: PathBuf::from("nginx"),
+            args: ["-p".into(), self.nginx_dir.clone().into_os_string(), "-c".into(), "nginx.conf".into(), "-g".into(), "daemon off;".into()].to_vec(),
+            env: Vec::new(),
+            pid_file: self.paths.nginx_pid(),
+            sockets: Vec::new(),
+            stop_signal: Signal::Quit,
+        })
+    }
+}
+
+pub struct ApacheService {
+    pub apache_dir: PathBuf,
+    paths: FurnacePaths,
+}
+
+/// Modules the generated httpd.conf loads, if present in the modules dir.
+const APACHE_MODULES: &[(&str, &str)] = &[
+    ("mpm_event_module", "mod_mpm_event.so"),
+    ("unixd_module", "mod_unixd.so"),
+    ("authz_core_module", "mod_authz_core.so"),
+    ("authz_host_module", "mod_authz_host.so"),
+    ("dir_module", "mod_dir.so"),
+    ("mime_module", "mod_mime.so"),
+    ("log_config_module", "mod_log_config.so"),
+    ("rewrite_module", "mod_rewrite.so"),
+    ("proxy_module", "mod_proxy.so"),
+    ("proxy_fcgi_module", "mod_proxy_fcgi.so"),
+    ("socache_shmcb_module", "mod_socache_shmcb.so"),
+    ("ssl_module", "mod_ssl.so"),
+];
+
+const APACHE_MODULE_DIRS: &[&str] = &[
+    "/opt/homebrew/opt/httpd/lib/httpd/modules",
+    "/usr/local/opt/httpd/lib/httpd/modules",
+    "/usr/lib/apache2/modules",
+    "/usr/lib64/httpd/modules",
+    "/usr/lib/httpd/modules",
+    "/usr/libexec/apache2",
+];
+
+const MIME_TYPES_FILES: &[&str] = &[
+    "/opt/homebrew/etc/httpd/mime.types",
+    "/usr/local/etc/httpd/mime.types",
+    "/etc/mime.types",
+    "/etc/httpd/conf/mime.types",
+];
+
+impl ApacheService {
+    pub fn new(paths: &FurnacePaths) -> Self {
+        ApacheService { apache_dir: paths.apache_dir(), paths: paths.clone() }
+    }
+
+    /// The httpd binary name on this system (`httpd` or Debian's `apache2`).
+    fn binary() -> &'static str {
+        #[cfg(target_os = "windows")]
+        return "httpd.exe";
+        #[cfg(not(target_os = "windows"))]
+        {
+            let found = |bin: &str| matches!(
+                Command::new("which").arg(bin).output(),
+                Ok(ref o) if o.status.success()
+            );
+            if !found("httpd") && found("apache2") { "apache2" } else { "httpd" }
+        }
+    }
+
+    pub fn main_conf_path(&self) -> PathBuf {
+        self.apache_dir.join("httpd.conf")
+    }
+
+    /// Run httpd against the Furnace-generated httpd.conf with extra arguments.
+    fn run(&self, args: &[&str]) -> Result<Output> {
+        let bin = Self::binary();
+        Command::new(bin)
+            .arg("-d").arg(&self.apache_dir)
+            .arg("-f").arg(self.main_conf_path())
+            .args(args)
+            .output()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => FurnaceError::BinaryNotFound(bin.to_string()),
+                _ => FurnaceError::Io(e),
+            })
+    }
+
+    /// Generate the Furnace-owned httpd.conf that includes every site vhost.
+    pub fn write_main_conf(&self) -> Result<()> {
+        let modules_dir = APACHE_MODULE_DIRS
+            .iter()
+            .map(PathBuf::from)
+            .find(|d| d.join("mod_proxy_fcgi.so").exists())
+            .ok_or_else(|| FurnaceError::BinaryNotFound("Apache mod_proxy_fcgi".to_string()))?;
+        let config = FurnaceConfig::load(&self.paths)?;
+        let logs_dir = self.paths.apache_logs_dir();
+        let sites_dir = self.paths.apache_sites_dir();
+        fs::create_dir_all(&logs_dir)?;
+        fs::create_dir_all(&sites_dir)?;
+
+        let mut modules = String::new();
+        for (name, file) in APACHE_MODULES {
+            let module_path = modules_dir.join(file);
+            if module_path.exists() {
+                modules.push_str(&format!(
+                    "<IfModule !{name}>\n    LoadModule {name} \"{}\"\n</IfModule>\n",
+                    module_path.to_string_lossy()
+                ));
+            }
+        }
+        let types_config = MIME_TYPES_FILES
+            .iter()
+            .find(|f| std::path::Path::new(f).exists())
+            .map(|f| format!("TypesConfig \"{}\"\n", f))
+            .unwrap_or_default();
+        // Only bind the HTTPS port when a site needs it; `serve` claims it on
+        // the same condition
+        let secured = recipe::get_recipes(&self.paths)?
+            .iter()
+            .any(|r| r.secure && r.serve_with == self.name());
+        let ssl = if secured {
+            format!(
+                "<IfModule ssl_module>\n    Listen {}\n    SSLSessionCache \"shmcb:{}/ssl_scache(512000)\"\n</IfModule>\n",
+                config.https_port,
+                logs_dir.to_string_lossy()
+            )
+        } else {
+            String::new()
+        };
+
+        let conf = format!(r#"# Main Furnace-managed httpd.conf
+# This file is generated and managed by Furnace.
+
+ServerRoot "{apache_dir}"
+PidFile "{logs_dir}/httpd.pid"
+Listen {http_port}
+ServerName localhost
+
+{modules}
+ErrorLog "{logs_dir}/error.log"
+LogLevel warn
+LogFormat "%h %l %u %t \"%r\" %>s %b \"%{{Referer}}i\" \"%{{User-Agent}}i\"" combined
+{types_config}DirectoryIndex index.php index.html
+
+{ssl}
+<Directory />
+    AllowOverride None
+    Require all denied
+</Directory>
+
+# Furnace-managed sites
+IncludeOptional "{sites_dir}/*.conf"
 "#,
-            site = recipe.site,
-            project_path = recipe.path,
+            apache_dir = self.apache_dir.to_string_lossy(),
             logs_dir = logs_dir.to_string_lossy(),
-            php_fpm_socket = php_fpm_socket,
-            project = recipe.name
+            sites_dir = sites_dir.to_string_lossy(),
+            http_port = config.http_port,
         );
-        fs::write(&conf_path, nginx_conf)
-            .map_err(|e| format!("Failed to write nginx conf: {e}"))?;
+        fs::write(self.main_conf_path(), conf)?;
         Ok(())
     }
-    fn reload(&self) -> Result<(), String> {
-        let status = Command::new("nginx")
-            .arg("-p").arg(&self.nginx_dir)
-            .arg("-c").arg("nginx.conf")
-            .arg("-s").arg("reload")
-            .status()
-            .map_err(|e| format!("Failed to reload nginx: {e}"))?;
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("nginx reload failed with status: {}", status))
-        }
-    }
 }
 
-pub struct ApacheService;
+/// Classify a failed httpd invocation, detecting port conflicts.
+pub fn apache_error(output: &Output) -> FurnaceError {
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    if stderr.contains("Address already in use") {
+        let port = stderr
+            .split("make_sock: could not bind to address ")
+            .nth(1)
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|addr| addr.rsplit(':').next())
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(80);
+        FurnaceError::PortInUse(port)
+    } else if stderr.contains("Syntax error") {
+        FurnaceError::ConfigValidation { service: "apache".to_string(), stderr }
+    } else {
+        FurnaceError::command_failed(ApacheService::binary(), output)
+    }
+}
 
 impl WebService for ApacheService {
+    fn name(&self) -> &'static str {
+        "apache"
+    }
     fn detect_installation() -> bool {
-        #[cfg(target_os = "macos")]
-        let output = std::process::Command::new("which").arg("httpd").output();
-        #[cfg(target_os = "linux")]
-        let output = std::process::Command::new("which").arg("apache2").output();
         #[cfg(target_os = "windows")]
-        let output = std::process::Command::new("where").arg("httpd.exe").output();
+        let output = Command::new("where").arg("httpd.exe").output();
+        #[cfg(not(target_os = "windows"))]
+        let output = Command::new("which").arg(Self::binary()).output();
         matches!(output, Ok(ref o) if o.status.success())
     }
-    fn start(&self) -> Result<(), String> {
-        // TODO: implement start logic
-        Ok(())
+    fn is_running(&self) -> bool {
+        status::process_status(&self.paths.apache_pid()).state == ProcessState::Running
     }
-    fn stop(&self) -> Result<(), String> {
-        // TODO: implement stop logic
-        Ok(())
+    /// Validate the Furnace httpd.conf with `httpd -t`.
+    fn test_config(&self) -> Result<()> {
+        self.write_main_conf()?;
+        let output = self.run(&["-t"])?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(apache_error(&output))
+        }
     }
-    fn write_conf(&self, _recipe: &Recipe) -> Result<(), String> {
-        // TODO: implement config writing logic
-        Ok(())
+    fn start(&self) -> Result<()> {
+        self.write_main_conf()?;
+        let output = self.run(&["-k", "start"])?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(apache_error(&output))
+        }
     }
-    fn reload(&self) -> Result<(), String> {
-        // TODO: implement reload logic
+    fn stop(&self) -> Result<()> {
+        if !self.paths.apache_pid().exists() {
+            return Ok(()); // Already stopped
+        }
+        let output = self.run(&["-k", "stop"])?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(apache_error(&output))
+        }
+    }
+    fn conf_path(&self, recipe_name: &str) -> PathBuf {
+        self.paths.apache_sites_dir().join(format!("{}.conf", recipe_name))
+    }
+    fn write_conf(&self, recipe: &Recipe, config: &FurnaceConfig) -> Result<()> {
+        fs::create_dir_all(self.paths.apache_sites_dir())?;
+        let logs_dir = self.paths.apache_logs_dir();
+        fs::create_dir_all(&logs_dir)?;
+        let apache_conf = template::render_vhost(&self.paths, config, Template::ApacheVhost, recipe, &logs_dir)?;
+        fs::write(self.conf_path(&recipe.name), apache_conf)?;
         Ok(())
     }
+    fn reload(&self) -> Result<()> {
+        let output = self.run(&["-k", "graceful"])?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(apache_error(&output))
+        }
+    }
+    fn foreground(&self) -> Result<ProcessSpec> {
+        self.write_main_conf()?;
+        Ok(ProcessSpec {
+            name: "apache".to_string(),
+            program: PathBuf::from(Self::binary()),
+            args: ["-d".into(), self.apache_dir.clone().into_os_string(), "-f".into(), self.main_conf_path().into_os_string(), "-DFOREGROUND".into()].to_vec(),
+            env: Vec::new(),
+            pid_file: self.paths.apache_pid(),
+            sockets: Vec::new(),
+            stop_signal: Signal::Term,
+        })
+    }
 }
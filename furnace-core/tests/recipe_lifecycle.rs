@@ -0,0 +1,148 @@
+use std::fs;
+use std::path::Path;
+
+use furnace_core::recipe::{self, CookOptions, RecipeDisposalTarget};
+use furnace_core::FurnacePaths;
+
+fn laravel_project(dir: &Path) {
+    fs::create_dir_all(dir.join("public")).unwrap();
+    fs::write(dir.join("artisan"), "").unwrap();
+    fs::write(dir.join("composer.json"), r#"{"require":{"php":"^8.2"}}"#).unwrap();
+}
+
+#[test]
+fn cook_and_dispose_in_isolated_home() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = FurnacePaths::resolve(Some(tmp.path().join("home"))).unwrap();
+    let project = tmp.path().join("shop");
+    laravel_project(&project);
+
+    let cooked = recipe::cook(&paths, &project, CookOptions::default()).unwrap();
+    assert_eq!(cooked.name, "shop");
+    assert_eq!(cooked.site, "shop.test");
+    assert_eq!(cooked.php_version, "8.2");
+    assert!(paths.recipe_file("shop").exists());
+    assert!(paths.nginx_servers_dir().join("shop.conf").exists());
+    assert_eq!(recipe::get_recipes(&paths).unwrap().len(), 1);
+
+    let report = recipe::dispose(&paths, RecipeDisposalTarget::ByPath(project.clone())).unwrap();
+    assert_eq!(report.name, "shop");
+    assert!(!paths.recipe_file("shop").exists());
+    assert!(!project.join(".furnace.recipe.yml").exists());
+    assert!(recipe::get_recipes(&paths).unwrap().is_empty());
+}
+
+#[test]
+fn dispose_unknown_recipe_fails() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = FurnacePaths::new(tmp.path());
+    let err = recipe::dispose(&paths, RecipeDisposalTarget::ByName("nope".into())).unwrap_err();
+    assert!(matches!(err, furnace_core::FurnaceError::RecipeNotFound(_)));
+}
+
+#[test]
+fn serve_with_selects_backend_config() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = FurnacePaths::new(tmp.path().join("home"));
+    let project = tmp.path().join("blog");
+    laravel_project(&project);
+
+    let opts = CookOptions { serve_with: Some("apache".into()), ..Default::default() };
+    recipe::cook(&paths, &project, opts).unwrap();
+    assert!(paths.apache_sites_dir().join("blog.conf").exists());
+    assert!(!paths.nginx_servers_dir().join("blog.conf").exists());
+
+    let report = recipe::dispose(&paths, RecipeDisposalTarget::ByName("blog".into())).unwrap();
+    assert!(report.removed.contains(&paths.apache_sites_dir().join("blog.conf")));
+    assert!(!paths.apache_sites_dir().join("blog.conf").exists());
+}
+
+#[test]
+fn unknown_serve_with_is_rejected() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = FurnacePaths::new(tmp.path().join("home"));
+    let project = tmp.path().join("legacy");
+    laravel_project(&project);
+
+    let opts = CookOptions { serve_with: Some("caddy".into()), ..Default::default() };
+    let err = recipe::cook(&paths, &project, opts).unwrap_err();
+    assert!(matches!(err, furnace_core::FurnaceError::UnknownBackend(_)));
+
+    fs::create_dir_all(paths.recipes_dir()).unwrap();
+    fs::write(
+        paths.recipe_file("legacy"),
+        "name: legacy\npath: /tmp\nphp_version: '8.2'\nserve_with: caddy\nsite: legacy.test\n",
+    )
+    .unwrap();
+    assert!(recipe::find_recipe(&paths, "legacy").is_err());
+    assert!(recipe::get_recipes(&paths).unwrap().is_empty());
+}
+
+#[test]
+fn wordpress_is_served_from_project_root() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = FurnacePaths::new(tmp.path().join("home"));
+    let project = tmp.path().join("blog");
+    fs::create_dir_all(&project).unwrap();
+    fs::write(project.join("wp-config.php"), "<?php").unwrap();
+
+    let recipe = recipe::cook(&paths, &project, CookOptions::default()).unwrap();
+    assert_eq!(recipe.driver, "wordpress");
+    let conf = fs::read_to_string(paths.nginx_servers_dir().join("blog.conf")).unwrap();
+    assert!(conf.contains(&format!("root {};", project.canonicalize().unwrap().display())));
+    assert!(conf.contains("deny all;"));
+}
+
+#[test]
+fn uninstall_moves_recipes_with_force_and_prune_drops_unused() {
+    use furnace_core::php;
+    use furnace_core::php_installation::{self, InstallKind, PhpInstallation};
+
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = FurnacePaths::new(tmp.path().join("home"));
+    for version in ["8.2", "8.3"] {
+        php_installation::record(&paths, &PhpInstallation {
+            version: version.into(),
+            kind: InstallKind::Linked,
+            php: format!("/usr/bin/php{}", version).into(),
+            fpm: format!("/usr/sbin/php-fpm{}", version).into(),
+            php_ini: None,
+            extension_dir: None,
+        })
+        .unwrap();
+        fs::create_dir_all(paths.php_version_dir(version)).unwrap();
+    }
+    let project = tmp.path().join("shop");
+    laravel_project(&project);
+    assert_eq!(recipe::cook(&paths, &project, CookOptions::default()).unwrap().php_version, "8.3");
+
+    let err = php::php_uninstall(&paths, "8.3", false).unwrap_err();
+    assert!(matches!(err, furnace_core::FurnaceError::PhpInUse { .. }));
+    let report = php::php_uninstall(&paths, "8.3", true).unwrap();
+    assert_eq!(report.reassigned[0].php_version, "8.2");
+    assert!(!paths.php_version_dir("8.3").exists());
+    assert_eq!(recipe::find_recipe(&paths, "shop").unwrap().php_version, "8.2");
+
+    assert!(php::php_prune(&paths).unwrap().is_empty());
+    recipe::dispose(&paths, RecipeDisposalTarget::ByName("shop".into())).unwrap();
+    let pruned = php::php_prune(&paths).unwrap();
+    assert_eq!(pruned[0].version, "8.2");
+    assert!(php_installation::load_manifest(&paths).unwrap().is_empty());
+}
+
+#[test]
+fn refresh_picks_up_project_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = FurnacePaths::new(tmp.path().join("home"));
+    let project = tmp.path().join("shop");
+    laravel_project(&project);
+    recipe::cook(&paths, &project, CookOptions::default()).unwrap();
+    recipe::set_orphaned(&paths, "shop", true).unwrap();
+
+    fs::write(project.join(".furnace.yml"), "php_version: \"8.1\"\nfpm_pool:\n  max_children: 8\n").unwrap();
+    let refreshed = recipe::refresh(&paths, "shop").unwrap();
+    assert_eq!(refreshed.php_version, "8.1");
+    assert!(!refreshed.orphaned);
+    let vhost = fs::read_to_string(paths.nginx_servers_dir().join("shop.conf")).unwrap();
+    assert!(vhost.contains(&paths.php_pool_socket("8.1", "shop").to_string_lossy().to_string()));
+}
@@ -1,25 +1,75 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
-use furnace_core::services;
+use std::path::PathBuf;
+
+use furnace_core::{
+    daemon::{CookRequest, DaemonClient},
+    recipe::{self, CookOptions, DisposeReport, Recipe, RecipeDisposalTarget, SecureReport},
+    services,
+    status::FurnaceStatus,
+    FurnaceError, FurnacePaths,
+};
+
+#[tauri::command]
+fn furnace_status() -> Result<FurnaceStatus, FurnaceError> {
+    let paths = FurnacePaths::from_env()?;
+    match DaemonClient::connect(&paths) {
+        Some(client) => client.status(),
+        None => services::status(&paths),
+    }
+}
+
+#[tauri::command]
+fn recipe_list() -> Result<Vec<Recipe>, FurnaceError> {
+    recipe::get_recipes(&FurnacePaths::from_env()?)
+}
+
+#[tauri::command]
+fn recipe_cook(
+    path: PathBuf,
+    name: Option<String>,
+    serve_with: Option<String>,
+    driver: Option<String>,
+) -> Result<Recipe, FurnaceError> {
+    let paths = FurnacePaths::from_env()?;
+    match DaemonClient::connect(&paths) {
+        Some(client) => client.cook(&CookRequest { path, name, serve_with, driver }),
+        None => recipe::cook(&paths, &path, CookOptions { name, serve_with, driver }),
+    }
+}
+
+#[tauri::command]
+fn recipe_secure(name: String) -> Result<SecureReport, FurnaceError> {
+    recipe::secure(&FurnacePaths::from_env()?, &name)
+}
 
 #[tauri::command]
-fn furnace_status() -> String {
-    // For now, just call the status function (which prints to stdout)
-    // In the future, refactor to return a structured status
-    services::status();
-    "Status checked (see logs)".to_string()
+fn recipe_unsecure(name: String) -> Result<Recipe, FurnaceError> {
+    recipe::unsecure(&FurnacePaths::from_env()?, &name)
 }
 
 #[tauri::command]
-fn recipe_list() -> Vec<furnace_core::recipe::Recipe> {
-    furnace_core::recipe::get_recipes()
+fn recipe_dispose(name: String) -> Result<DisposeReport, FurnaceError> {
+    let paths = FurnacePaths::from_env()?;
+    let target = RecipeDisposalTarget::ByName(name);
+    match DaemonClient::connect(&paths) {
+        Some(client) => client.dispose(&target),
+        None => recipe::dispose(&paths, target),
+    }
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![furnace_status, recipe_list])
+        .invoke_handler(tauri::generate_handler![
+            furnace_status,
+            recipe_list,
+            recipe_cook,
+            recipe_secure,
+            recipe_unsecure,
+            recipe_dispose
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -6,27 +6,6 @@ import "./App.css";
 import FurnaceTemplate from "./components/FurnaceTemplate";
 import Welcome from "./pages/Welcome";
 
-function FurnaceStatus() {
-  const [status, setStatus] = useState("");
-
-  const checkStatus = async () => {
-    try {
-      const result = await invoke<string>("furnace_status");
-      setStatus(result);
-    } catch (e) {
-      setStatus("Error: " + e);
-    }
-  };
-
-  return (
-    <div>
-      <button onClick={checkStatus}>Check Furnace Status</button>
-      <div>Status: {status}</div>
-    </div>
-  );
-}
-
-
 function App() {
   const [greetMsg, setGreetMsg] = useState("");
   const [name, setName] = useState("");
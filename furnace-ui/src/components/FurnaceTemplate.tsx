@@ -1,4 +1,5 @@
 import { useState } from "react";
+import StatusPanel from "./StatusPanel";
 
 // Dummy data for recipes
 const dummyRecipes = [
@@ -50,6 +51,7 @@ export default function FurnaceTemplate() {
           <div><b>PHP Version:</b> {dummyRecipes[selected].php_version}</div>
           <div><b>Serve With:</b> {dummyRecipes[selected].serve_with}</div>
         </div>
+        <StatusPanel />
       </main>
     </div>
   );
@@ -0,0 +1,84 @@
+import { useEffect, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { FurnaceStatus, ProcessState } from "../types/status";
+
+const stateColor: Record<ProcessState, string> = {
+  running: "#16a34a",
+  stale: "#d97706",
+  stopped: "#64748b",
+};
+
+function StateBadge({ state }: { state: ProcessState }) {
+  return <span style={{ color: stateColor[state], fontWeight: 600 }}>{state}</span>;
+}
+
+export default function StatusPanel() {
+  const [status, setStatus] = useState<FurnaceStatus | null>(null);
+  const [error, setError] = useState("");
+
+  const checkStatus = async () => {
+    try {
+      setStatus(await invoke<FurnaceStatus>("furnace_status"));
+      setError("");
+    } catch (e) {
+      setError("Error: " + e);
+    }
+  };
+
+  useEffect(() => {
+    checkStatus();
+  }, []);
+
+  return (
+    <div style={{ background: "#f1f5f9", padding: 20, borderRadius: 8, maxWidth: 480, marginTop: 24 }}>
+      <div style={{ display: "flex", alignItems: "center", marginBottom: 12 }}>
+        <span style={{ fontWeight: 600, flex: 1 }}>Services</span>
+        <button onClick={checkStatus} style={{ background: "#e0e7ef", border: "none", borderRadius: 6, padding: "4px 10px", cursor: "pointer" }}>
+          Refresh
+        </button>
+      </div>
+      {error && <div style={{ color: "#dc2626" }}>{error}</div>}
+      {status && (
+        <>
+          <div>
+            <b>nginx:</b> <StateBadge state={status.nginx.state} />
+            {status.nginx.pid !== null && <> (PID {status.nginx.pid})</>}
+          </div>
+          <div>
+            <b>apache:</b> <StateBadge state={status.apache.state} />
+            {status.apache.pid !== null && <> (PID {status.apache.pid})</>}
+          </div>
+          {status.php_pools.map((pool) => (
+            <div key={pool.version}>
+              <b>php-fpm {pool.version}:</b> <StateBadge state={pool.process.state} />
+              {pool.responding ? " — responding" : pool.socket_exists ? " — socket not responding" : " — no socket"}
+            </div>
+          ))}
+          <div>
+            <b>dns ({status.dns.provider}):</b> {status.dns.configured ? "configured" : "not configured"}
+            {status.dns.server !== null && (
+              <>
+                {" — dnsmasq "}
+                <StateBadge state={status.dns.server.state} />
+              </>
+            )}
+          </div>
+          {status.recipes.length > 0 && (
+            <ul style={{ listStyle: "none", padding: 0, marginTop: 12 }}>
+              {status.recipes.map((recipe) => (
+                <li key={recipe.name}>
+                  <b>{recipe.name}</b>:{" "}
+                  {recipe.healthy ? (
+                    <span style={{ color: stateColor.running }}>healthy</span>
+                  ) : (
+                    <span style={{ color: "#dc2626" }}>{recipe.issues.join(", ")}</span>
+                  )}
+                </li>
+              ))}
+            </ul>
+          )}
+        </>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,43 @@
+export type ProcessState = "running" | "stale" | "stopped";
+
+export type ProcessStatus = {
+  pid_file: string;
+  pid: number | null;
+  state: ProcessState;
+};
+
+export type PhpPoolStatus = {
+  version: string;
+  process: ProcessStatus;
+  socket: string;
+  socket_exists: boolean;
+  responding: boolean;
+};
+
+export type DnsProviderKind = "dnsmasq" | "resolved" | "hosts";
+
+export type DnsStatus = {
+  provider: DnsProviderKind;
+  server: ProcessStatus | null;
+  configured: boolean;
+  details: string;
+};
+
+export type RecipeHealth = {
+  name: string;
+  site: string;
+  php_version: string;
+  serve_with: string;
+  secure: boolean;
+  orphaned: boolean;
+  healthy: boolean;
+  issues: string[];
+};
+
+export type FurnaceStatus = {
+  nginx: ProcessStatus;
+  apache: ProcessStatus;
+  php_pools: PhpPoolStatus[];
+  dns: DnsStatus;
+  recipes: RecipeHealth[];
+};